@@ -1,13 +1,10 @@
-use chrono::NaiveDate;
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::{footer_amounts, PrintedTotals};
 
-pub(crate) fn _extract_payment_data(
-    pdf_file: &std::path::PathBuf,
-) -> (
-    Vec<(NaiveDate, f64, f64, f64, f64, f64, f64)>,
-    Vec<usize>,
-    Vec<usize>,
-) {
-    let text = pdf_extract::extract_text(pdf_file).unwrap();
+pub(crate) fn _extract_payment_data(text: &str) -> Schedule {
+    let locale = Locale::detect(text);
     let lines = text.lines().collect::<Vec<_>>();
 
     let date_line_indexes = lines
@@ -15,14 +12,7 @@ pub(crate) fn _extract_payment_data(
         .enumerate()
         .filter(|(_, line)| {
             let words = line.split_whitespace().collect::<Vec<_>>();
-            if words.len() < 3 {
-                return false;
-            }
-            NaiveDate::parse_from_str(
-                &(format!("{} {} {}", words[0], words[1], words[2])),
-                "%d %b %Y",
-            )
-            .is_ok()
+            locale.date_prefix(&words).is_some()
         })
         .map(|(i, _)| i)
         .collect::<Vec<_>>();
@@ -37,59 +27,28 @@ pub(crate) fn _extract_payment_data(
         .filter(|line| line.split_whitespace().collect::<Vec<_>>().len() >= 19)
         .collect::<Vec<_>>();
 
-    let mut total_principal = 0.0;
-    let mut total_interest = 0.0;
-
-    let mut max_local_performance = 0.0;
-    let mut max_local_performance_indexes = Vec::new();
-    let mut max_global_performance = 0.0;
-    let mut max_global_performance_indexes = Vec::new();
-
-    let payment_data: Vec<(NaiveDate, f64, f64, f64, f64, f64, f64)> = concatenated_lines
+    let installments = concatenated_lines
         .iter()
-        .enumerate()
-        .map(|(i, line)| {
+        .filter_map(|line| {
             let words = line.split_whitespace().collect::<Vec<_>>();
-            let date = format!("{} {} {}", words[0], words[1], words[2]);
-            let date = NaiveDate::parse_from_str(&date, "%d %b %Y").unwrap();
-
-            let local_principal = words[4].replace(",", "").parse().unwrap();
-            let local_interest = words[6].replace(",", "").parse().unwrap();
-            let local_performance = local_interest / local_principal * 100.0;
-            if local_performance > max_local_performance {
-                max_local_performance = local_performance;
-                max_local_performance_indexes.clear();
-                max_local_performance_indexes.push(i);
-            } else if local_performance == max_local_performance {
-                max_local_performance_indexes.push(i);
-            }
+            let (date, length) = locale.date_prefix(&words)?;
+            let amounts = locale.amounts(&words[length..]);
 
-            total_principal += local_principal;
-            total_interest += local_interest;
-            let total_performance = total_interest / total_principal * 100.0;
-            if total_performance > max_global_performance {
-                max_global_performance = total_performance;
-                max_global_performance_indexes.clear();
-                max_global_performance_indexes.push(i);
-            } else if total_performance == max_global_performance {
-                max_global_performance_indexes.push(i);
-            }
+            let principal = *amounts.get(1)?;
+            let interest = *amounts.get(3)?;
 
-            (
-                date,
-                local_principal,
-                local_interest,
-                local_performance,
-                total_principal,
-                total_interest,
-                total_performance,
-            )
+            Some(Installment::new(date, principal, interest))
         })
         .collect();
 
-    (
-        payment_data,
-        max_local_performance_indexes,
-        max_global_performance_indexes,
-    )
+    Schedule {
+        loan: LoanInfo::from_header(text, locale),
+        totals: footer_amounts(text, locale).map(|amounts| PrintedTotals {
+            principal: amounts.get(1).copied(),
+            interest: amounts.get(3).copied(),
+            insurance: None,
+            commission: None,
+        }),
+        ..Schedule::new(installments)
+    }
 }
@@ -1,13 +1,236 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
+
+use crate::daycount::DayCount;
+use crate::deferral::Capitalization;
 
 #[derive(Parser)]
 #[command(name = "Calculator de rambursari anticipate")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
+#[command(subcommand_precedence_over_arg = true)]
+#[command(subcommand_negates_reqs = true)]
 pub(crate) struct Arguments {
+    /// Scadențarele de analizat: fișiere, directoare sau modele ca „scadentare/*.pdf”
+    #[arg(value_parser = check_input_path, required_unless_present = "list_banks", num_args = 1..)]
+    pub(crate) input_files: Vec<PathBuf>,
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+    /// Suma rambursată anticipat, pentru simularea cu dobândă calculată zilnic
+    #[arg(long, global = true)]
+    pub(crate) prepayment: Option<f64>,
+    /// Convenția de calcul a zilelor pentru dobânda zilnică
+    #[arg(long, value_enum, default_value = "act/365", global = true)]
+    pub(crate) day_count: DayCount,
+    /// Prima zi în care se poate face rambursarea anticipată
+    #[arg(long, value_parser = parse_date, requires_all = ["prepayment", "window_end"], global = true)]
+    pub(crate) window_start: Option<NaiveDate>,
+    /// Ultima zi în care se poate face rambursarea anticipată
+    #[arg(long, value_parser = parse_date, requires_all = ["prepayment", "window_start"], global = true)]
+    pub(crate) window_end: Option<NaiveDate>,
+    /// Numărul de zile de preaviz cerut de bancă (implicit, cel al băncii)
+    #[arg(long, global = true)]
+    pub(crate) notice_days: Option<u64>,
+    /// Banca aplică rambursările anticipate doar la scadență (implicit, regula băncii)
+    #[arg(long, value_name = "true|false", global = true)]
+    pub(crate) only_on_due_dates: Option<bool>,
+    /// Dobânda anuală (%) obținută pe sumă până la rambursare
+    #[arg(long, default_value_t = 0.0, global = true)]
+    pub(crate) deposit_rate: f64,
+    /// Suma lunară disponibilă pentru rambursări anticipate, împărțită între toate creditele
+    #[arg(long, value_parser = parse_positive_amount, global = true)]
+    pub(crate) budget: Option<f64>,
+    /// Salvează scadențarul citit în format JSON, lângă fișierul de intrare
+    #[arg(long, global = true)]
+    pub(crate) export_json: bool,
+    /// Afișează băncile recunoscute: cele incluse, profilele și pluginurile
+    #[arg(long, global = true)]
+    pub(crate) list_banks: bool,
+    /// Parola fișierelor PDF protejate (sau variabila de mediu PDF_PASSWORD; altfel, este cerută la
+    /// deschidere)
+    #[arg(long, global = true)]
+    pub(crate) password: Option<String>,
+    /// Nu cere nimic în consolă (parole, apăsarea unei taste la final), pentru rularea din scripturi
+    #[arg(long, global = true)]
+    pub(crate) non_interactive: bool,
+    #[command(flatten)]
+    pub(crate) import: ImportArguments,
+}
+
+#[derive(Args)]
+pub(crate) struct ImportArguments {
+    /// Separatorul coloanelor din fișierele CSV
+    #[arg(long, value_parser = parse_delimiter, default_value_t = ',', global = true)]
+    pub(crate) csv_delimiter: char,
+    /// Foaia de calcul din fișierele XLSX/ODS (numele sau numărul foii, de la 1; implicit, prima)
+    #[arg(long, global = true)]
+    pub(crate) sheet: Option<String>,
+    /// Zona din foaia de calcul care conține scadențarul (de exemplu A5:H300)
+    #[arg(long, value_parser = parse_cell_range, global = true)]
+    pub(crate) range: Option<CellRange>,
+    /// Formatul datelor scrise ca text (implicit, recunoscut din fișier)
+    #[arg(long, global = true)]
+    pub(crate) csv_date_format: Option<String>,
+    /// Separatorul zecimal al sumelor scrise ca text (implicit, recunoscut din fișier)
+    #[arg(long, global = true)]
+    pub(crate) csv_decimal_separator: Option<char>,
+    /// Coloana cu data scadenței (numele din antet sau numărul coloanei, de la 1)
+    #[arg(long, value_parser = parse_column, default_value = "Data", global = true)]
+    pub(crate) csv_date_column: Column,
+    /// Coloana cu principalul
+    #[arg(long, value_parser = parse_column, default_value = "Principal", global = true)]
+    pub(crate) csv_principal_column: Column,
+    /// Coloana cu dobânda
+    #[arg(long, value_parser = parse_column, default_value = "Dobanda", global = true)]
+    pub(crate) csv_interest_column: Column,
+    /// Coloana cu asigurarea
+    #[arg(long, value_parser = parse_column, global = true)]
+    pub(crate) csv_insurance_column: Option<Column>,
+    /// Coloana cu comisioanele
+    #[arg(long, value_parser = parse_column, global = true)]
+    pub(crate) csv_commission_column: Option<Column>,
+    /// Coloana cu soldul rămas după plata ratei
+    #[arg(long, value_parser = parse_column, global = true)]
+    pub(crate) csv_balance_column: Option<Column>,
+}
+
+// zero based (row, column) of the first and last cell
+pub(crate) type CellRange = ((u32, u32), (u32, u32));
+
+#[derive(Clone, Debug)]
+pub(crate) enum Column {
+    Index(usize),
+    Header(String),
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Compară creditul actual cu o ofertă de refinanțare
+    Compare(CompareArguments),
+    /// Simulează amânarea la plată a unor rate (vacanță de rate)
+    Defer(DeferArguments),
+    /// Compară scadențarul cu o versiune nouă a lui, emisă de bancă după o rambursare anticipată sau o modificare a dobânzii
+    Diff(DiffArguments),
+    /// Adaugă scadențarul în istoricul creditului și afișează evoluția acestuia
+    History(HistoryArguments),
+    /// Potrivește plățile din extrasul de cont (CSV sau MT940) cu ratele din scadențar
+    Reconcile(ReconcileArguments),
+    /// Urmărește directoarele date și analizează scadențarele noi sau modificate, scriind rapoartele lângă ele
+    Watch(WatchArguments),
+}
+
+#[derive(Args)]
+pub(crate) struct CompareArguments {
+    /// Suma creditului nou (implicit, capitalul rămas de rambursat)
+    #[arg(long)]
+    pub(crate) amount: Option<f64>,
+    /// Dobânda anuală a ofertei (%)
+    #[arg(long)]
+    pub(crate) rate: f64,
+    /// Numărul de rate lunare (implicit, numărul de rate rămase)
+    #[arg(long)]
+    pub(crate) term: Option<u32>,
+    /// Comisionul lunar de administrare
+    #[arg(long, default_value_t = 0.0)]
+    pub(crate) monthly_fee: f64,
+    /// Asigurarea lunară
+    #[arg(long, default_value_t = 0.0)]
+    pub(crate) insurance: f64,
+    /// Costul evaluării imobilului
+    #[arg(long, default_value_t = 0.0)]
+    pub(crate) valuation: f64,
+    /// Onorariul notarului
+    #[arg(long, default_value_t = 0.0)]
+    pub(crate) notary: f64,
+    /// Alte costuri inițiale
+    #[arg(long, default_value_t = 0.0)]
+    pub(crate) other_costs: f64,
+    /// Comisionul de rambursare anticipată al băncii actuale
+    #[arg(long, default_value_t = 0.0)]
+    pub(crate) exit_fee: f64,
+    /// Data refinanțării (implicit, astăzi)
+    #[arg(long, value_parser = parse_date)]
+    pub(crate) from: Option<NaiveDate>,
+}
+
+#[derive(Args)]
+pub(crate) struct DeferArguments {
+    /// Data de la care se amână ratele
+    #[arg(long, value_parser = parse_date)]
+    pub(crate) from: NaiveDate,
+    /// Numărul de rate amânate
+    #[arg(long)]
+    pub(crate) count: usize,
+    /// Modul în care este capitalizată dobânda amânată
+    #[arg(long, value_enum, default_value = "added")]
+    pub(crate) capitalization: Capitalization,
+    /// Păstrează data ultimei rate în loc să prelungească creditul cu perioada amânată
+    #[arg(long)]
+    pub(crate) keep_term: bool,
+    /// Dobânda anuală (%) a dobânzii capitalizate (implicit, cea din antetul scadențarului, altfel
+    /// cea care rezultă din rate)
+    #[arg(long)]
+    pub(crate) rate: Option<f64>,
+}
+
+#[derive(Args)]
+pub(crate) struct DiffArguments {
+    /// Scadențarul vechi
+    #[arg(value_parser = check_if_path_exists)]
+    pub(crate) old_file: PathBuf,
+    /// Scadențarul nou
+    #[arg(value_parser = check_if_path_exists)]
+    pub(crate) new_file: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct HistoryArguments {
+    /// Numele creditului în istoric (implicit, numărul contractului sau numele fișierului)
+    #[arg(long)]
+    pub(crate) loan: Option<String>,
+    /// Suma rambursată anticipat, adăugată în istoric
+    #[arg(long)]
+    pub(crate) prepaid: Option<f64>,
+    /// Suma plătită pentru o rată, adăugată în istoric
+    #[arg(long)]
+    pub(crate) paid: Option<f64>,
+    /// Data rambursării sau a plății (implicit, astăzi)
+    #[arg(long, value_parser = parse_date)]
+    pub(crate) on: Option<NaiveDate>,
+    /// Adaugă suma chiar dacă aceeași sumă din aceeași zi este deja în istoric (de exemplu, două rate
+    /// plătite în aceeași zi)
+    #[arg(long)]
+    pub(crate) force: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct ReconcileArguments {
+    /// Extrasul de cont exportat din internet banking (CSV sau MT940)
     #[arg(value_parser = check_if_path_exists)]
-    pub(crate) input_file: PathBuf,
+    pub(crate) statement: PathBuf,
+    /// Textul din descrierea plăților creditului (implicit, cuvinte ca „rata” sau „rambursare anticipată”)
+    #[arg(long)]
+    pub(crate) description: Option<String>,
+    /// Coloana cu suma tranzacției din fișierele CSV
+    #[arg(long, value_parser = parse_column, default_value = "Suma")]
+    pub(crate) amount_column: Column,
+    /// Coloana cu descrierea tranzacției din fișierele CSV (implicit, găsită după antet)
+    #[arg(long, value_parser = parse_column)]
+    pub(crate) description_column: Option<Column>,
+    /// Numărul de zile înainte de scadență din care o plată este pentru rata respectivă
+    #[arg(long, default_value_t = 10)]
+    pub(crate) early_days: u64,
+}
+
+#[derive(Args)]
+pub(crate) struct WatchArguments {
+    /// Directoarele urmărite
+    #[arg(value_parser = check_if_path_exists, required = true, num_args = 1..)]
+    pub(crate) directories: Vec<PathBuf>,
+    /// Intervalul, în secunde, la care sunt verificate directoarele
+    #[arg(long, default_value_t = 30)]
+    pub(crate) interval: u64,
 }
 
 fn check_if_path_exists(path: &str) -> Result<PathBuf, String> {
@@ -17,4 +240,69 @@ fn check_if_path_exists(path: &str) -> Result<PathBuf, String> {
     } else {
         Err(format!("Fișierul nu există : {}", path.display()))
     }
-}
\ No newline at end of file
+}
+
+// glob patterns are expanded later, the Windows console leaves them as they are
+fn check_input_path(path: &str) -> Result<PathBuf, String> {
+    match path.contains(['*', '?', '[']) {
+        true => Ok(PathBuf::from(path)),
+        false => check_if_path_exists(path),
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%d.%m.%Y")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .map_err(|_| format!("Data nu este validă (zz.ll.aaaa) : {}", date))
+}
+
+fn parse_positive_amount(amount: &str) -> Result<f64, String> {
+    match amount.parse::<f64>() {
+        Ok(amount) if amount > 0.0 && amount.is_finite() => Ok(amount),
+        _ => Err(format!("Suma trebuie să fie mai mare decât 0 : {}", amount)),
+    }
+}
+
+// the CSV reader takes a single byte, so the separator must be an ASCII character
+fn parse_delimiter(delimiter: &str) -> Result<char, String> {
+    let mut chars = delimiter.chars();
+    match (chars.next(), chars.next()) {
+        (Some(delimiter), None) if delimiter.is_ascii() => Ok(delimiter),
+        _ => Err(format!(
+            "Separatorul trebuie să fie un singur caracter ASCII : {}",
+            delimiter
+        )),
+    }
+}
+
+fn parse_column(column: &str) -> Result<Column, String> {
+    match column.parse::<usize>() {
+        Ok(0) => Err("Numerotarea coloanelor începe de la 1".to_string()),
+        Ok(index) => Ok(Column::Index(index)),
+        Err(_) => Ok(Column::Header(column.to_string())),
+    }
+}
+
+fn parse_cell_range(range: &str) -> Result<CellRange, String> {
+    let parse_cell = |cell: &str| {
+        let letters = cell
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect::<String>();
+        let row = cell[letters.len()..].parse::<u32>().ok()?.checked_sub(1)?;
+        let column = letters
+            .to_ascii_uppercase()
+            .chars()
+            .try_fold(0u32, |column, c| {
+                Some(column * 26 + (c as u32 - 'A' as u32 + 1))
+            })?
+            .checked_sub(1)?;
+        Some((row, column))
+    };
+    let error = || format!("Zona nu este validă (de exemplu A5:H300) : {}", range);
+    let (start, end) = range.split_once(':').ok_or_else(error)?;
+    Ok((
+        parse_cell(start.trim()).ok_or_else(error)?,
+        parse_cell(end.trim()).ok_or_else(error)?,
+    ))
+}
@@ -0,0 +1,361 @@
+use std::path::Path;
+
+use pdf_extract::Document;
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::pdftable::{self, TableLayout};
+use crate::plugin::ParserPlugin;
+use crate::prepayment::PrepaymentRules;
+use crate::profile::ParserProfile;
+use crate::schedule::Schedule;
+use crate::table::print_table;
+use crate::{
+    alphabankexported, bcrbank, brdbank, btbank, cecbank, ingbank, librabank, otpbank,
+    raiffeisenbank, unicreditbank,
+};
+
+// the variants are named as the files of each bank start ("alphabank_scadentar.pdf")
+#[allow(clippy::upper_case_acronyms, clippy::enum_variant_names)]
+#[derive(EnumIter, Debug, Clone, Copy)]
+pub(crate) enum Bank {
+    AlphaBankExported,
+    AlphaBank,
+    ING,
+    // before BT, OTP schedules issued after the merger also mention Banca Transilvania
+    OTP,
+    BT,
+    BCR,
+    BRD,
+    Raiffeisen,
+    UniCredit,
+    CEC,
+    Libra,
+}
+
+impl Bank {
+    pub(crate) fn determine_bank(file_name: &str) -> Option<Bank> {
+        let file_name = file_name.to_ascii_lowercase();
+        Bank::iter().find(|bank| file_name.starts_with(&bank.name()))
+    }
+
+    // the bank whose name appears in the schedule, for files that were not renamed
+    pub(crate) fn detect_bank(text: &str) -> Option<Bank> {
+        let text = text.to_lowercase();
+        Bank::iter().find(|bank| {
+            bank.keywords()
+                .iter()
+                .any(|keyword| contains_word(&text, &keyword.to_lowercase()))
+        })
+    }
+
+    pub(crate) fn name(&self) -> String {
+        format!("{:?}", self).to_ascii_lowercase()
+    }
+
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Bank::AlphaBankExported => &["Alpha Bank"],
+            Bank::AlphaBank => &[],
+            // "ING Bank" alone is also found in "issuing bank" or "paying bank"
+            Bank::ING => &["ING Bank N.V.", "INGBROBU"],
+            Bank::BT => &["Banca Transilvania"],
+            Bank::BCR => &["Banca Comerciala Romana", "Banca Comercială Română"],
+            Bank::BRD => &["BRD Groupe", "BRD - Groupe"],
+            Bank::Raiffeisen => &["Raiffeisen"],
+            Bank::UniCredit => &["UniCredit"],
+            Bank::CEC => &["CEC Bank"],
+            Bank::Libra => &["Libra Internet Bank"],
+            Bank::OTP => &["OTP Bank"],
+        }
+    }
+
+    // only the rules of the banks whose prepayment conditions were checked are known, the other
+    // banks use the default (any day, no notice) until --only-on-due-dates and --notice-days are given
+    pub(crate) fn prepayment_rules(&self) -> PrepaymentRules {
+        match self {
+            Bank::AlphaBankExported | Bank::AlphaBank => PrepaymentRules {
+                only_on_due_dates: true,
+                notice_days: 10,
+            },
+            Bank::ING => PrepaymentRules {
+                only_on_due_dates: false,
+                notice_days: 0,
+            },
+            _ => PrepaymentRules::default(),
+        }
+    }
+
+    // the columns of the schedule table, rebuilt from the position of the text on the page
+    fn table_layout(&self) -> TableLayout {
+        match self {
+            Bank::AlphaBankExported | Bank::AlphaBank => TableLayout {
+                date: "Data",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: None,
+                commission: None,
+                balance: None,
+            },
+            Bank::ING => TableLayout {
+                date: "Data",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: Some("Asigurare"),
+                commission: None,
+                balance: None,
+            },
+            Bank::BT => TableLayout {
+                date: "Data scadentei",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: Some("Asigurare"),
+                commission: Some("Comision administrare"),
+                balance: Some("Sold credit"),
+            },
+            Bank::BCR => TableLayout {
+                date: "Data",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: Some("Asigurare"),
+                commission: Some("Comision de administrare"),
+                balance: Some("Sold"),
+            },
+            Bank::BRD => TableLayout {
+                date: "Data scadenta",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: Some("Asigurare"),
+                commission: None,
+                balance: Some("Sold final"),
+            },
+            Bank::Raiffeisen => TableLayout {
+                date: "Data scadenta",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: Some("Asigurare"),
+                commission: Some("Comision administrare"),
+                balance: Some("Sold"),
+            },
+            Bank::UniCredit => TableLayout {
+                date: "Data scadenta",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: Some("Asigurare"),
+                commission: Some("Comision"),
+                balance: Some("Sold ramas"),
+            },
+            // the balance column is the one before the installment, the text parser computes the
+            // balance after it
+            Bank::CEC => TableLayout {
+                date: "Data scadenta",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: None,
+                commission: Some("Comision"),
+                balance: None,
+            },
+            Bank::Libra => TableLayout {
+                date: "Data",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: Some("Asigurare"),
+                commission: None,
+                balance: Some("Sold"),
+            },
+            Bank::OTP => TableLayout {
+                date: "Data",
+                principal: "Principal",
+                interest: "Dobanda",
+                insurance: Some("Asigurare"),
+                commission: Some("Comision"),
+                balance: Some("Sold"),
+            },
+        }
+    }
+
+    // the table layer is tried first, the text of the PDF is used when the table is not found
+    pub(crate) fn extract_payment_data(&self, document: &Document, text: &str) -> Schedule {
+        let table_schedule = match pdftable::extract_schedule(document, &self.table_layout()) {
+            Ok(schedule) => schedule.filter(|schedule| !schedule.installments.is_empty()),
+            Err(error) => {
+                eprintln!(
+                    "Tabelul scadențarului nu a putut fi citit, se folosește textul lui : {}",
+                    error
+                );
+                None
+            }
+        };
+        if let Some(schedule) = table_schedule {
+            return Schedule {
+                loan: LoanInfo::from_header(text, Locale::detect(text)),
+                ..schedule
+            };
+        }
+        match self {
+            Bank::AlphaBankExported => alphabankexported::extract_payment_data(text),
+            Bank::AlphaBank => alphabankexported::extract_payment_data(text),
+            Bank::ING => ingbank::extract_payment_data(text),
+            Bank::BT => btbank::extract_payment_data(text),
+            Bank::BCR => bcrbank::extract_payment_data(text),
+            Bank::BRD => brdbank::extract_payment_data(text),
+            Bank::Raiffeisen => raiffeisenbank::extract_payment_data(text),
+            Bank::UniCredit => unicreditbank::extract_payment_data(text),
+            Bank::CEC => cecbank::extract_payment_data(text),
+            Bank::Libra => librabank::extract_payment_data(text),
+            Bank::OTP => otpbank::extract_payment_data(text),
+        }
+    }
+}
+
+// the profiles and plugins next to the executable, read once for all the files
+pub(crate) struct Parsers {
+    pub(crate) profiles: Vec<ParserProfile>,
+    pub(crate) plugins: Vec<ParserPlugin>,
+}
+
+// a built-in parser, one described by a profile file or an external program
+pub(crate) enum Extractor {
+    Bank(Bank),
+    Profile(Box<ParserProfile>),
+    Plugin(Box<ParserPlugin>),
+}
+
+// `keyword` appears in `text` as whole words, not as the end or the beginning of longer ones
+pub(crate) fn contains_word(text: &str, keyword: &str) -> bool {
+    if keyword.is_empty() {
+        return false;
+    }
+    let is_word_character = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    text.match_indices(keyword).any(|(position, _)| {
+        !is_word_character(text[..position].chars().next_back())
+            && !is_word_character(text[position + keyword.len()..].chars().next())
+    })
+}
+
+fn contains_keyword(text: &str, keywords: &[String]) -> bool {
+    keywords
+        .iter()
+        .any(|keyword| contains_word(text, &keyword.to_lowercase()))
+}
+
+impl Extractor {
+    // profiles and plugins come first so that a built-in layout can be replaced without recompiling
+    pub(crate) fn determine_extractor(
+        file_name: &str,
+        text: &str,
+        profiles: &[ParserProfile],
+        plugins: &[ParserPlugin],
+    ) -> Option<Extractor> {
+        let file_name = file_name.to_ascii_lowercase();
+        let lowercase_text = text.to_lowercase();
+        let profile = |profile: &ParserProfile| Extractor::Profile(Box::new(profile.clone()));
+        let plugin = |plugin: &ParserPlugin| Extractor::Plugin(Box::new(plugin.clone()));
+        profiles
+            .iter()
+            .find(|profile| file_name.starts_with(&profile.name.to_ascii_lowercase()))
+            .map(profile)
+            .or_else(|| {
+                plugins
+                    .iter()
+                    .find(|plugin| file_name.starts_with(&plugin.name.to_ascii_lowercase()))
+                    .map(plugin)
+            })
+            .or_else(|| Bank::determine_bank(&file_name).map(Extractor::Bank))
+            .or_else(|| {
+                profiles
+                    .iter()
+                    .find(|profile| contains_keyword(&lowercase_text, &profile.keywords))
+                    .map(profile)
+            })
+            .or_else(|| {
+                plugins
+                    .iter()
+                    .find(|plugin| contains_keyword(&lowercase_text, &plugin.keywords))
+                    .map(plugin)
+            })
+            .or_else(|| Bank::detect_bank(text).map(Extractor::Bank))
+    }
+
+    pub(crate) fn name(&self) -> String {
+        match self {
+            Extractor::Bank(bank) => bank.name(),
+            Extractor::Profile(profile) => profile.name.to_ascii_lowercase(),
+            Extractor::Plugin(plugin) => plugin.name.to_ascii_lowercase(),
+        }
+    }
+
+    pub(crate) fn prepayment_rules(&self) -> PrepaymentRules {
+        match self {
+            Extractor::Bank(bank) => bank.prepayment_rules(),
+            Extractor::Profile(profile) => profile.prepayment_rules(),
+            Extractor::Plugin(plugin) => plugin.prepayment_rules(),
+        }
+    }
+
+    pub(crate) fn extract_payment_data(
+        &self,
+        pdf_file: &Path,
+        document: &Document,
+        text: &str,
+    ) -> Result<Schedule, String> {
+        match self {
+            Extractor::Bank(bank) => Ok(bank.extract_payment_data(document, text)),
+            Extractor::Profile(profile) => Ok(profile.extract_payment_data(text)),
+            Extractor::Plugin(plugin) => plugin.extract_payment_data(pdf_file, text),
+        }
+    }
+}
+
+// every parser the program knows about, with the keywords used to recognize its schedules
+pub(crate) fn print_banks(profiles: &[ParserProfile], plugins: &[ParserPlugin]) {
+    let rows = Bank::iter()
+        .map(|bank| {
+            vec![
+                bank.name(),
+                "inclus".to_string(),
+                bank.keywords().join(", "),
+            ]
+        })
+        .chain(profiles.iter().map(|profile| {
+            vec![
+                profile.name.to_ascii_lowercase(),
+                "profil".to_string(),
+                profile.keywords.join(", "),
+            ]
+        }))
+        .chain(plugins.iter().map(|plugin| {
+            vec![
+                plugin.name.to_ascii_lowercase(),
+                format!("plugin ({})", plugin.command),
+                plugin.keywords.join(", "),
+            ]
+        }))
+        .collect::<Vec<_>>();
+    print_table(&["Bancă", "Tip", "Cuvinte cheie"], &rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_the_bank_from_whole_words() {
+        assert!(matches!(
+            Bank::detect_bank("ING Bank N.V. Amsterdam - Sucursala Bucuresti"),
+            Some(Bank::ING)
+        ));
+        assert!(Bank::detect_bank("Plata prin lending bank sau issuing bank").is_none());
+        assert!(!contains_word("grafic de rambursare", ""));
+        // the schedules issued by OTP after the merger also name Banca Transilvania
+        assert!(matches!(
+            Bank::detect_bank(include_str!("../tests/fixtures/otp.txt")),
+            Some(Bank::OTP)
+        ));
+        assert!(matches!(
+            Bank::detect_bank(include_str!("../tests/fixtures/cec.txt")),
+            Some(Bank::CEC)
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::schedule::Schedule;
+use crate::table::print_table;
+
+// a processed file with the bank and the schedule read from it, none when it failed
+pub(crate) type FileResult = (PathBuf, Option<(Option<String>, Schedule)>);
+
+// the files a schedule is read from
+const SCHEDULE_EXTENSIONS: [&str; 6] = ["pdf", "csv", "xlsx", "xls", "ods", "json"];
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+}
+
+fn is_pattern(input: &Path) -> bool {
+    input.to_string_lossy().contains(['*', '?', '['])
+}
+
+// the schedules in a directory and its subdirectories; a JSON file saved with --export-json
+// next to the schedule it was read from is skipped
+pub(crate) fn directory_files(directory: &Path) -> Vec<PathBuf> {
+    let mut files = WalkDir::new(directory)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            extension(path)
+                .is_some_and(|extension| SCHEDULE_EXTENSIONS.contains(&extension.as_str()))
+        })
+        .collect::<Vec<_>>();
+    let exported = files
+        .iter()
+        .filter(|path| {
+            extension(path).as_deref() == Some("json")
+                && files
+                    .iter()
+                    .any(|other| other != *path && other.with_extension("json") == **path)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    files.retain(|path| !exported.contains(path));
+    files
+}
+
+// the input files, with directories walked and glob patterns ("scadentare/*.pdf") expanded,
+// since the Windows console does not expand them
+pub(crate) fn expand_input_files(inputs: &[PathBuf]) -> Vec<PathBuf> {
+    inputs
+        .iter()
+        .flat_map(|input| {
+            if input.is_dir() {
+                return directory_files(input);
+            }
+            if input.exists() || !is_pattern(input) {
+                return vec![input.clone()];
+            }
+            let files = match glob::glob(&input.to_string_lossy()) {
+                Ok(paths) => paths
+                    .filter_map(|path| path.ok())
+                    .flat_map(|path| match path.is_dir() {
+                        true => directory_files(&path),
+                        false => vec![path],
+                    })
+                    .collect::<Vec<_>>(),
+                Err(error) => {
+                    eprintln!("Modelul {} nu este valid : {}", input.display(), error);
+                    return Vec::new();
+                }
+            };
+            if files.is_empty() {
+                eprintln!("Niciun fișier nu corespunde modelului {}", input.display());
+            }
+            files
+        })
+        .collect()
+}
+
+// one row for every file, with the principal and interest still to be paid, and the totals of
+// every currency
+pub(crate) fn print_batch_summary(results: &[FileResult]) {
+    let today = chrono::Local::now().date_naive();
+    let mut totals: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    let mut rows = results
+        .iter()
+        .map(|(input_file, result)| {
+            let name = input_file.display().to_string();
+            let Some((bank, schedule)) = result else {
+                return vec![
+                    name,
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    "eroare".to_string(),
+                ];
+            };
+            let remaining = schedule
+                .installments
+                .iter()
+                .filter(|installment| installment.date > today)
+                .collect::<Vec<_>>();
+            let principal = remaining.iter().fold(0.0, |principal, installment| {
+                principal + installment.principal
+            });
+            let interest = remaining
+                .iter()
+                .fold(0.0, |interest, installment| interest + installment.interest);
+            let currency = schedule.currency();
+            let total = totals.entry(currency).or_insert((0.0, 0.0));
+            total.0 += principal;
+            total.1 += interest;
+            vec![
+                name,
+                bank.clone().unwrap_or_default(),
+                remaining.len().to_string(),
+                format!("{:.2} {}", principal, currency),
+                format!("{:.2} {}", interest, currency),
+                schedule
+                    .installments
+                    .last()
+                    .map(|installment| installment.date.to_string())
+                    .unwrap_or_default(),
+                "ok".to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let failed = results
+        .iter()
+        .filter(|(_, result)| result.is_none())
+        .count();
+    rows.extend(totals.iter().map(|(currency, (principal, interest))| {
+        vec![
+            "Total".to_string(),
+            String::new(),
+            String::new(),
+            format!("{:.2} {}", principal, currency),
+            format!("{:.2} {}", interest, currency),
+            String::new(),
+            String::new(),
+        ]
+    }));
+
+    println!();
+    print_table(
+        &[
+            "Fișier",
+            "Bancă",
+            "Rate rămase",
+            "Capital rămas",
+            "Dobândă rămasă",
+            "Ultima rată",
+            "Stare",
+        ],
+        &rows,
+    );
+    println!("{} fișiere procesate, {} cu erori.", results.len(), failed);
+}
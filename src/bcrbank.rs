@@ -0,0 +1,84 @@
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::{footer_amounts, PrintedTotals};
+
+pub(crate) fn extract_payment_data(text: &str) -> Schedule {
+    let locale = Locale::detect(text);
+    // Nr. | Data | Rata | Dobanda | Principal | Comision de administrare | Asigurare | Sold
+    let installments = text
+        .lines()
+        .filter_map(|line| {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            words.first()?.parse::<usize>().ok()?;
+            let (date, length) = locale.date_prefix(&words[1..])?;
+            let amounts = locale.amounts(&words[1 + length..]);
+            if amounts.len() != 6 {
+                return None;
+            }
+
+            let interest = amounts[1];
+            let principal = amounts[2];
+            let commission = amounts[3];
+            let insurance = amounts[4];
+            let balance = amounts[5];
+
+            Some(Installment {
+                insurance,
+                commission,
+                balance: Some(balance),
+                ..Installment::new(date, principal, interest)
+            })
+        })
+        .collect();
+
+    Schedule {
+        loan: LoanInfo::from_header(text, locale),
+        // the total row leaves the balance empty:
+        // Total | Rata | Dobanda | Principal | Comision de administrare | Asigurare
+        totals: footer_amounts(text, locale).map(|amounts| PrintedTotals {
+            principal: amounts.get(2).copied(),
+            interest: amounts.get(1).copied(),
+            insurance: amounts.get(4).copied(),
+            commission: amounts.get(3).copied(),
+        }),
+        ..Schedule::new(installments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/bcr.txt");
+
+    #[test]
+    fn reads_the_interest_before_the_principal() {
+        let schedule = extract_payment_data(FIXTURE);
+        assert_eq!(schedule.installments.len(), 2);
+
+        let first = &schedule.installments[0];
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2024, 2, 15).unwrap());
+        assert_eq!(first.principal, 320.0);
+        assert_eq!(first.interest, 750.0);
+        assert_eq!(first.commission, 15.0);
+        assert_eq!(first.insurance, 15.0);
+        assert_eq!(first.balance, Some(149680.0));
+        assert_eq!(schedule.installments[1].balance, Some(149358.4));
+    }
+
+    #[test]
+    fn reads_the_total_row_and_the_header() {
+        let schedule = extract_payment_data(FIXTURE);
+        let totals = schedule.totals.unwrap();
+        assert_eq!(totals.principal, Some(641.6));
+        assert_eq!(totals.interest, Some(1498.4));
+        assert_eq!(totals.commission, Some(30.0));
+        assert_eq!(totals.insurance, Some(30.0));
+        assert_eq!(schedule.loan.contract.as_deref(), Some("BCR-2024-0815"));
+        assert_eq!(schedule.loan.amount, Some(150000.0));
+        assert_eq!(schedule.loan.term, Some(360));
+    }
+}
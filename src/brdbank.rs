@@ -0,0 +1,107 @@
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::{footer_amounts, PrintedTotals};
+
+pub(crate) fn extract_payment_data(text: &str) -> Schedule {
+    let locale = Locale::detect(text);
+    // Nr. rata | Data scadenta | Sold initial | Rata | Principal | Dobanda | Asigurare | Sold final
+    // after a partial prepayment the schedule starts from the next installment number with the
+    // reduced balance, so neither the numbering nor the balance are assumed to start from the loan
+    let installments = text
+        .lines()
+        .filter_map(|line| {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            words.first()?.parse::<usize>().ok()?;
+            let (date, length) = locale.date_prefix(&words[1..])?;
+            let amounts = locale.amounts(&words[1 + length..]);
+            if amounts.len() != 6 {
+                return None;
+            }
+
+            let principal = amounts[2];
+            let interest = amounts[3];
+            let insurance = amounts[4];
+            let balance = amounts[5];
+
+            Some(Installment {
+                insurance,
+                balance: Some(balance),
+                ..Installment::new(date, principal, interest)
+            })
+        })
+        .collect();
+
+    Schedule {
+        loan: LoanInfo::from_header(text, locale),
+        // the total row leaves the balance empty:
+        // Total | Rata | Principal | Dobanda | Asigurare
+        totals: footer_amounts(text, locale).map(|amounts| PrintedTotals {
+            principal: amounts.get(1).copied(),
+            interest: amounts.get(2).copied(),
+            insurance: amounts.get(3).copied(),
+            commission: None,
+        }),
+        ..Schedule::new(installments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn reads_the_installments_and_the_total_row() {
+        let schedule = extract_payment_data(include_str!("../tests/fixtures/brd.txt"));
+        assert_eq!(schedule.installments.len(), 2);
+
+        // the printed balance is the one after the installment
+        let first = &schedule.installments[0];
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2024, 3, 5).unwrap());
+        assert_eq!(first.principal, 450.0);
+        assert_eq!(first.interest, 980.0);
+        assert_eq!(first.insurance, 20.0);
+        assert_eq!(first.balance, Some(199550.0));
+
+        let totals = schedule.totals.unwrap();
+        assert_eq!(totals.principal, Some(902.21));
+        assert_eq!(totals.interest, Some(1957.79));
+        assert_eq!(totals.insurance, Some(40.0));
+        assert_eq!(schedule.loan.contract.as_deref(), Some("2024/77120"));
+    }
+
+    #[test]
+    fn reads_schedules_issued_after_a_partial_prepayment() {
+        let schedule = extract_payment_data(include_str!("../tests/fixtures/brd-prepayment.txt"));
+        assert_eq!(
+            schedule
+                .installments
+                .iter()
+                .map(|installment| (
+                    installment.date,
+                    installment.principal,
+                    installment.interest,
+                    installment.balance
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    NaiveDate::from_ymd_opt(2026, 3, 5).unwrap(),
+                    300.0,
+                    588.0,
+                    Some(119700.0)
+                ),
+                (
+                    NaiveDate::from_ymd_opt(2026, 4, 5).unwrap(),
+                    301.47,
+                    586.53,
+                    Some(119398.53)
+                ),
+            ]
+        );
+        // the loan amount of the header is the one of the contract, not the reduced balance
+        assert_eq!(schedule.loan.amount, Some(200000.0));
+    }
+}
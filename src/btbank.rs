@@ -0,0 +1,90 @@
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::{footer_amounts, PrintedTotals};
+
+pub(crate) fn extract_payment_data(text: &str) -> Schedule {
+    let locale = Locale::detect(text);
+    // the table is repeated on every page with its header, keep only the installment rows:
+    // Nr. | Data scadentei | Sold credit | Principal | Dobanda | Comision administrare | Asigurare | Total rata
+    let installments = text
+        .lines()
+        .filter_map(|line| {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            words.first()?.parse::<usize>().ok()?;
+            let (date, length) = locale.date_prefix(&words[1..])?;
+            let amounts = locale.amounts(&words[1 + length..]);
+            if amounts.len() < 6 {
+                return None;
+            }
+
+            let balance = amounts[0];
+            let principal = amounts[1];
+            let interest = amounts[2];
+            let commission = amounts[3];
+            let insurance = amounts[4];
+
+            Some(Installment {
+                insurance,
+                commission,
+                balance: Some(balance),
+                ..Installment::new(date, principal, interest)
+            })
+        })
+        .collect();
+
+    Schedule {
+        loan: LoanInfo::from_header(text, locale),
+        // the total row leaves the balance empty:
+        // Total | Principal | Dobanda | Comision administrare | Asigurare | Total rata
+        totals: footer_amounts(text, locale).map(|amounts| PrintedTotals {
+            principal: amounts.first().copied(),
+            interest: amounts.get(1).copied(),
+            insurance: amounts.get(3).copied(),
+            commission: amounts.get(2).copied(),
+        }),
+        ..Schedule::new(installments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/bt.txt");
+
+    #[test]
+    fn reads_the_installments_of_every_page() {
+        let schedule = extract_payment_data(FIXTURE);
+        assert_eq!(schedule.installments.len(), 6);
+
+        let first = &schedule.installments[0];
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2024, 2, 15).unwrap());
+        assert_eq!(first.principal, 1103.17);
+        assert_eq!(first.interest, 781.25);
+        assert_eq!(first.commission, 25.0);
+        assert_eq!(first.insurance, 31.5);
+        assert_eq!(first.balance, Some(148896.83));
+
+        // the first row after the header repeated on the second page
+        let fourth = &schedule.installments[3];
+        assert_eq!(fourth.date, NaiveDate::from_ymd_opt(2024, 5, 15).unwrap());
+        assert_eq!(fourth.principal, 1112.68);
+        assert_eq!(fourth.interest, 763.96);
+        assert_eq!(fourth.balance, Some(145568.3));
+    }
+
+    #[test]
+    fn reads_the_total_row_and_the_header() {
+        let schedule = extract_payment_data(FIXTURE);
+        let totals = schedule.totals.unwrap();
+        assert_eq!(totals.principal, Some(6666.57));
+        assert_eq!(totals.interest, Some(4600.98));
+        assert_eq!(totals.commission, Some(150.0));
+        assert_eq!(totals.insurance, Some(189.0));
+        assert_eq!(schedule.loan.contract.as_deref(), Some("2024/BT/778812"));
+        assert_eq!(schedule.loan.amount, Some(150000.0));
+    }
+}
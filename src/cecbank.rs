@@ -0,0 +1,86 @@
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::{footer_amounts, PrintedTotals};
+
+pub(crate) fn extract_payment_data(text: &str) -> Schedule {
+    let locale = Locale::detect(text);
+    // Nr. | Data scadenta | Sold | Rata | Principal | Dobanda | Comision
+    let installments = text
+        .lines()
+        .filter_map(|line| {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            words.first()?.parse::<usize>().ok()?;
+            let (date, length) = locale.date_prefix(&words[1..])?;
+            let amounts = locale.amounts(&words[1 + length..]);
+            if amounts.len() != 5 {
+                return None;
+            }
+
+            let balance_before = amounts[0];
+            let principal = amounts[2];
+            let interest = amounts[3];
+            let commission = amounts[4];
+
+            Some(Installment {
+                commission,
+                balance: Some(balance_before - principal),
+                ..Installment::new(date, principal, interest)
+            })
+        })
+        .collect();
+
+    Schedule {
+        loan: LoanInfo::from_header(text, locale),
+        // the total row leaves the balance empty:
+        // Total | Rata | Principal | Dobanda | Comision
+        totals: footer_amounts(text, locale).map(|amounts| PrintedTotals {
+            principal: amounts.get(1).copied(),
+            interest: amounts.get(2).copied(),
+            insurance: None,
+            commission: amounts.get(3).copied(),
+        }),
+        ..Schedule::new(installments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/cec.txt");
+
+    #[test]
+    fn reads_the_installments_of_every_page() {
+        let schedule = extract_payment_data(FIXTURE);
+        assert_eq!(schedule.installments.len(), 3);
+
+        // the printed balance is the one before the installment
+        let first = &schedule.installments[0];
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2024, 3, 20).unwrap());
+        assert_eq!(first.principal, 400.0);
+        assert_eq!(first.interest, 600.0);
+        assert_eq!(first.commission, 20.0);
+        assert_eq!(first.balance, Some(99600.0));
+
+        let third = &schedule.installments[2];
+        assert_eq!(third.date, NaiveDate::from_ymd_opt(2024, 5, 20).unwrap());
+        assert_eq!(third.principal, 404.81);
+        assert_eq!(third.interest, 595.19);
+        assert!((third.balance.unwrap() - 98792.79).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reads_the_total_row_and_the_header() {
+        let schedule = extract_payment_data(FIXTURE);
+        let totals = schedule.totals.unwrap();
+        assert_eq!(totals.principal, Some(1207.21));
+        assert_eq!(totals.interest, Some(1792.79));
+        assert_eq!(totals.commission, Some(60.0));
+        assert_eq!(totals.insurance, None);
+        assert_eq!(schedule.loan.contract.as_deref(), Some("4471/2024"));
+        assert_eq!(schedule.loan.amount, Some(100000.0));
+    }
+}
@@ -0,0 +1,361 @@
+use std::io::Read;
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+
+use crate::arguments::{Column, ImportArguments};
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+
+// an explicit date format wins over the one recognized from the file
+pub(crate) fn parse_date(
+    value: &str,
+    date_format: Option<&str>,
+    locale: Locale,
+) -> Option<NaiveDate> {
+    match date_format {
+        Some(date_format) => NaiveDate::parse_from_str(value.trim(), date_format).ok(),
+        None => locale.parse_date(value),
+    }
+}
+
+// the locale of the values written as text, unless the separator was given
+pub(crate) fn detect_locale<'a>(
+    values: impl Iterator<Item = &'a str>,
+    decimal_separator: Option<char>,
+) -> Locale {
+    let text = values.collect::<Vec<_>>().join(" ");
+    let detected = Locale::detect(&text);
+    Locale {
+        decimal_separator: decimal_separator.unwrap_or(detected.decimal_separator),
+        ..detected
+    }
+}
+
+// the amount written in a cell: none when the cell is empty or only a dash, the text of the cell
+// when it is not an amount
+pub(crate) fn text_amount(text: &str, locale: Locale) -> Result<Option<f64>, String> {
+    let text = text.trim();
+    if text.chars().all(|c| matches!(c, '-' | '–' | '—')) {
+        return Ok(None);
+    }
+    locale
+        .parse_amount(text)
+        .map(Some)
+        .ok_or_else(|| text.to_string())
+}
+
+// the usual names of every column of a schedule export, compared as `header_key` returns them
+const DATE_HEADERS: [&str; 8] = [
+    "data",
+    "data scadenta",
+    "data scadentei",
+    "scadenta",
+    "data platii",
+    "date",
+    "due date",
+    "payment date",
+];
+const PRINCIPAL_HEADERS: [&str; 7] = [
+    "principal",
+    "rata principal",
+    "rata de principal",
+    "capital",
+    "rata capital",
+    "rata de capital",
+    "principal amount",
+];
+const INTEREST_HEADERS: [&str; 5] = [
+    "dobanda",
+    "dobanzi",
+    "rata dobanda",
+    "dobanda lunara",
+    "interest",
+];
+const INSURANCE_HEADERS: [&str; 4] = ["asigurare", "asigurari", "prima de asigurare", "insurance"];
+const COMMISSION_HEADERS: [&str; 6] = [
+    "comision",
+    "comisioane",
+    "comision administrare",
+    "comision de administrare",
+    "commission",
+    "fees",
+];
+const BALANCE_HEADERS: [&str; 7] = [
+    "sold",
+    "sold credit",
+    "sold ramas",
+    "sold final",
+    "balance",
+    "remaining balance",
+    "outstanding balance",
+];
+
+// a header without case, diacritics and punctuation, so "Data scadenței:" is "data scadentei"
+pub(crate) fn header_key(header: &str) -> String {
+    header
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'ă' | 'â' => 'a',
+            'î' => 'i',
+            'ș' | 'ş' => 's',
+            'ț' | 'ţ' => 't',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// a column given by name is looked up by its key; a name among `synonyms` also finds the column
+// under any of the other usual names, so "Data" finds "Data scadenței"
+pub(crate) fn column_index(
+    column: &Column,
+    headers: &[String],
+    synonyms: &[&str],
+) -> Result<usize, String> {
+    match column {
+        Column::Index(index) => Ok(index.saturating_sub(1)),
+        Column::Header(name) => {
+            let key = header_key(name);
+            let position = |names: &[&str]| {
+                headers
+                    .iter()
+                    .position(|header| names.contains(&header_key(header).as_str()))
+            };
+            position(&[key.as_str()])
+                .or_else(|| {
+                    synonyms
+                        .contains(&key.as_str())
+                        .then(|| position(synonyms))
+                        .flatten()
+                })
+                .ok_or_else(|| format!("Coloana \"{}\" nu există în antet.", name))
+        }
+    }
+}
+
+// where every column of the schedule is, shared by the CSV and spreadsheet imports
+pub(crate) struct ColumnMapping {
+    date: usize,
+    principal: usize,
+    interest: usize,
+    insurance: Option<usize>,
+    commission: Option<usize>,
+    balance: Option<usize>,
+}
+
+impl ColumnMapping {
+    pub(crate) fn new(
+        arguments: &ImportArguments,
+        headers: &[String],
+    ) -> Result<ColumnMapping, String> {
+        let optional_column = |column: &Option<Column>, synonyms: &[&str]| {
+            column
+                .as_ref()
+                .map(|column| column_index(column, headers, synonyms))
+                .transpose()
+        };
+        Ok(ColumnMapping {
+            date: column_index(&arguments.csv_date_column, headers, &DATE_HEADERS)?,
+            principal: column_index(&arguments.csv_principal_column, headers, &PRINCIPAL_HEADERS)?,
+            interest: column_index(&arguments.csv_interest_column, headers, &INTEREST_HEADERS)?,
+            insurance: optional_column(&arguments.csv_insurance_column, &INSURANCE_HEADERS)?,
+            commission: optional_column(&arguments.csv_commission_column, &COMMISSION_HEADERS)?,
+            balance: optional_column(&arguments.csv_balance_column, &BALANCE_HEADERS)?,
+        })
+    }
+
+    // whether the columns are looked up in a header row, rather than all given by number
+    pub(crate) fn uses_headers(arguments: &ImportArguments) -> bool {
+        [
+            Some(&arguments.csv_date_column),
+            Some(&arguments.csv_principal_column),
+            Some(&arguments.csv_interest_column),
+            arguments.csv_insurance_column.as_ref(),
+            arguments.csv_commission_column.as_ref(),
+            arguments.csv_balance_column.as_ref(),
+        ]
+        .iter()
+        .any(|column| matches!(column, Some(Column::Header(_))))
+    }
+
+    // the installment on row `row_number`, none for the rows without a valid date (headers
+    // repeated in the export, notes or totals); `date` and `amount` read the cell of a column,
+    // `amount` gives the text of a cell that is not an amount
+    pub(crate) fn installment(
+        &self,
+        row_number: u64,
+        date: impl Fn(usize) -> Option<NaiveDate>,
+        amount: impl Fn(usize) -> Result<Option<f64>, String>,
+    ) -> Option<Result<Installment, String>> {
+        let date = date(self.date)?;
+        Some(self.read_installment(row_number, date, amount))
+    }
+
+    // a dated row with an amount that cannot be read is a parsing problem, not a note
+    fn read_installment(
+        &self,
+        row_number: u64,
+        date: NaiveDate,
+        amount: impl Fn(usize) -> Result<Option<f64>, String>,
+    ) -> Result<Installment, String> {
+        let read = |column: Option<usize>, name: &str| match column.map(&amount) {
+            Some(Err(text)) => Err(format!(
+                "Rândul {}: {} \"{}\" nu este o sumă.",
+                row_number, name, text
+            )),
+            Some(Ok(value)) => Ok(value),
+            None => Ok(None),
+        };
+        let principal = read(Some(self.principal), "principalul")?.ok_or_else(|| {
+            format!(
+                "Principalul de pe rândul {} nu a putut fi citit.",
+                row_number
+            )
+        })?;
+        // an empty interest, insurance or commission cell is nothing to pay
+        Ok(Installment {
+            insurance: read(self.insurance, "asigurarea")?.unwrap_or(0.0),
+            commission: read(self.commission, "comisionul")?.unwrap_or(0.0),
+            balance: read(self.balance, "soldul")?,
+            ..Installment::new(
+                date,
+                principal,
+                read(Some(self.interest), "dobânda")?.unwrap_or(0.0),
+            )
+        })
+    }
+}
+
+pub(crate) fn import_schedule(
+    csv_file: &PathBuf,
+    arguments: &ImportArguments,
+) -> Result<Schedule, String> {
+    let file = std::fs::File::open(csv_file)
+        .map_err(|error| format!("Fișierul CSV nu a putut fi citit : {}", error))?;
+    read_schedule(file, arguments)
+}
+
+fn read_schedule(csv: impl Read, arguments: &ImportArguments) -> Result<Schedule, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(arguments.csv_delimiter as u8)
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(csv);
+    let headers = reader
+        .headers()
+        .map_err(|error| format!("Fișierul CSV nu a putut fi citit : {}", error))?
+        .iter()
+        .map(|header| header.to_string())
+        .collect::<Vec<_>>();
+    let columns = ColumnMapping::new(arguments, &headers)?;
+
+    let records = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| match error.position() {
+            Some(position) => format!(
+                "Rândul {} din fișierul CSV nu a putut fi citit : {}",
+                position.line(),
+                error
+            ),
+            None => format!("Fișierul CSV nu a putut fi citit : {}", error),
+        })?;
+    let locale = detect_locale(
+        records.iter().flat_map(|record| record.iter()),
+        arguments.csv_decimal_separator,
+    );
+    let date_format = arguments.csv_date_format.as_deref();
+    let installments = records
+        .iter()
+        .enumerate()
+        .filter_map(|(index, record)| {
+            let line = record
+                .position()
+                .map_or(index as u64 + 2, |position| position.line());
+            columns.installment(
+                line,
+                |column| parse_date(record.get(column)?, date_format, locale),
+                |column| {
+                    record
+                        .get(column)
+                        .map_or(Ok(None), |cell| text_amount(cell, locale))
+                },
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| format!("Fișierul CSV nu a putut fi citit : {}", error))?;
+
+    if installments.is_empty() {
+        return Err("Fișierul CSV nu conține nicio rată cu data validă.".to_string());
+    }
+    Ok(Schedule::new(installments))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::arguments::Arguments;
+
+    fn import_arguments(options: &[&str]) -> ImportArguments {
+        let command_line = ["calculator"]
+            .iter()
+            .chain(options)
+            .chain(&["scadentar*.csv"]);
+        Arguments::try_parse_from(command_line).unwrap().import
+    }
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn finds_columns_without_case_diacritics_or_punctuation() {
+        let headers = headers(&["Nr.", "Data scadenței:", "Rata de capital", "DOBÂNDĂ"]);
+        let column = |name: &str, synonyms: &[&str]| {
+            column_index(&Column::Header(name.to_string()), &headers, synonyms)
+        };
+        assert_eq!(column("data scadentei", &[]), Ok(1));
+        assert_eq!(column("Dobanda", &INTEREST_HEADERS), Ok(3));
+        // the default names also find the usual variants
+        assert_eq!(column("Data", &DATE_HEADERS), Ok(1));
+        assert_eq!(column("Principal", &PRINCIPAL_HEADERS), Ok(2));
+        // a name that is not a usual one must be in the header
+        assert!(column("Capital datorat", &PRINCIPAL_HEADERS).is_err());
+        assert_eq!(column_index(&Column::Index(2), &headers, &[]), Ok(1));
+    }
+
+    #[test]
+    fn reports_the_rows_that_cannot_be_read() {
+        let arguments = import_arguments(&["--csv-delimiter", ";"]);
+        let csv =
+            "Data;Principal;Dobanda\n15.01.2024;100,00;-\n15.02.2024;101,00;\nTotal;201,00;0\n";
+        let schedule = read_schedule(csv.as_bytes(), &arguments).unwrap();
+        assert_eq!(schedule.installments.len(), 2);
+        assert_eq!(schedule.installments[1].interest, 0.0);
+
+        let csv = "Data;Principal;Dobanda\n15.01.2024;100,00;50,00\n15.02.2024;101,00;n/a\n";
+        assert_eq!(
+            read_schedule(csv.as_bytes(), &arguments).err().as_deref(),
+            Some("Fișierul CSV nu a putut fi citit : Rândul 3: dobânda \"n/a\" nu este o sumă.")
+        );
+
+        let csv = "Data;Principal;Dobanda\n15.01.2024;;50,00\n";
+        assert_eq!(
+            read_schedule(csv.as_bytes(), &arguments).err().as_deref(),
+            Some("Fișierul CSV nu a putut fi citit : Principalul de pe rândul 2 nu a putut fi citit.")
+        );
+
+        let csv = b"Data;Principal;Dobanda\n15.01.2024;100,00;50,00\n15.02.2024;\xff;50,00\n";
+        let error = read_schedule(&csv[..], &arguments)
+            .err()
+            .unwrap_or_default();
+        assert!(error.starts_with("Rândul 3 din fișierul CSV nu a putut fi citit"));
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{Datelike, NaiveDate};
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum DayCount {
+    #[value(name = "act/365")]
+    Actual365,
+    #[value(name = "act/360")]
+    Actual360,
+    #[value(name = "30/360")]
+    Thirty360,
+}
+
+impl DayCount {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            DayCount::Actual365 => "act/365",
+            DayCount::Actual360 => "act/360",
+            DayCount::Thirty360 => "30/360",
+        }
+    }
+
+    pub(crate) fn days_between(&self, start: NaiveDate, end: NaiveDate) -> f64 {
+        match self {
+            DayCount::Actual365 | DayCount::Actual360 => (end - start).num_days() as f64,
+            DayCount::Thirty360 => {
+                // 30E/360: day 31 is treated as day 30 on both ends
+                let start_day = start.day().min(30) as i64;
+                let end_day = end.day().min(30) as i64;
+                let years = end.year() as i64 - start.year() as i64;
+                let months = end.month() as i64 - start.month() as i64;
+                (360 * years + 30 * months + end_day - start_day) as f64
+            }
+        }
+    }
+
+    pub(crate) fn days_in_year(&self) -> f64 {
+        match self {
+            DayCount::Actual365 => 365.0,
+            DayCount::Actual360 | DayCount::Thirty360 => 360.0,
+        }
+    }
+
+    pub(crate) fn year_fraction(&self, start: NaiveDate, end: NaiveDate) -> f64 {
+        self.days_between(start, end) / self.days_in_year()
+    }
+
+    // simple interest accrued daily on a constant balance
+    pub(crate) fn accrued_interest(
+        &self,
+        balance: f64,
+        annual_rate: f64,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> f64 {
+        balance * annual_rate * self.year_fraction(start, end)
+    }
+
+    // the annual rate that produces the given interest over the period
+    pub(crate) fn implied_annual_rate(
+        &self,
+        balance: f64,
+        interest: f64,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> f64 {
+        let year_fraction = self.year_fraction(start, end);
+        if balance <= 0.0 || year_fraction <= 0.0 {
+            return 0.0;
+        }
+        interest / (balance * year_fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn accrues_interest_by_convention() {
+        // 30 actual days in the leap year 2024, 31 days for 30E/360
+        let (start, end) = (date(2024, 1, 31), date(2024, 3, 1));
+        let interest = |day_count: DayCount| day_count.accrued_interest(100000.0, 0.06, start, end);
+        assert!((interest(DayCount::Actual365) - 6000.0 * 30.0 / 365.0).abs() < 1e-9);
+        assert!((interest(DayCount::Actual360) - 500.0).abs() < 1e-9);
+        assert!((interest(DayCount::Thirty360) - 6000.0 * 31.0 / 360.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn counts_day_31_as_day_30_for_30_360() {
+        let days = |start, end| DayCount::Thirty360.days_between(start, end);
+        assert_eq!(days(date(2024, 1, 31), date(2024, 2, 29)), 29.0);
+        assert_eq!(days(date(2024, 3, 31), date(2024, 4, 30)), 30.0);
+        assert_eq!(days(date(2023, 12, 15), date(2024, 1, 15)), 30.0);
+    }
+
+    #[test]
+    fn finds_the_rate_of_the_interest() {
+        let (start, end) = (date(2024, 1, 15), date(2024, 2, 15));
+        for day_count in [
+            DayCount::Actual365,
+            DayCount::Actual360,
+            DayCount::Thirty360,
+        ] {
+            let interest = day_count.accrued_interest(100000.0, 0.06, start, end);
+            let rate = day_count.implied_annual_rate(100000.0, interest, start, end);
+            assert!((rate - 0.06).abs() < 1e-12);
+        }
+        assert_eq!(
+            DayCount::Actual365.implied_annual_rate(0.0, 100.0, start, end),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use chrono::Months;
+use clap::ValueEnum;
+
+use crate::arguments::DeferArguments;
+use crate::daycount::DayCount;
+use crate::prepayment::accrual_periods;
+use crate::schedule::{annuity_schedule, Installment, Schedule};
+use crate::table::print_table;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub(crate) enum Capitalization {
+    // the deferred interest is added to the balance and bears interest
+    Added,
+    // the deferred interest is split equally over the remaining installments, without interest (OUG 37/2020)
+    Spread,
+}
+
+impl Capitalization {
+    fn name(&self) -> &'static str {
+        match self {
+            Capitalization::Added => "dobânda amânată se adaugă la sold",
+            Capitalization::Spread => "dobânda amânată se împarte egal pe ratele rămase",
+        }
+    }
+}
+
+// the schedule after deferring `count` installments starting with the first one due on or after `from`
+pub(crate) fn deferred_schedule(
+    schedule: &Schedule,
+    arguments: &DeferArguments,
+    day_count: DayCount,
+) -> Result<Schedule, String> {
+    let periods = accrual_periods(schedule, day_count);
+    let first = schedule
+        .installments
+        .iter()
+        .position(|installment| installment.date >= arguments.from)
+        .ok_or_else(|| format!("Nu există rate de amânat după data {}.", arguments.from))?;
+    let count = arguments.count.min(schedule.installments.len() - first);
+    // the capitalized interest is repaid with the installments left after the holiday
+    if arguments.keep_term && first + count == schedule.installments.len() {
+        return Err(format!(
+            "Cu --keep-term, după cele {} rate amânate trebuie să rămână cel puțin o rată.",
+            count
+        ));
+    }
+    let balance = periods[first].balance;
+    // the rate given, otherwise the one in the header, otherwise the one of every period
+    let rate = arguments
+        .rate
+        .or(schedule.loan.annual_rate)
+        .map(|rate| rate / 100.0);
+
+    // the balance stays the same during the holiday, only insurance and commissions are paid
+    let mut installments = schedule.installments[..first].to_vec();
+    let mut deferred_interest = 0.0;
+    schedule.installments[first..first + count]
+        .iter()
+        .zip(&periods[first..first + count])
+        .for_each(|(installment, period)| {
+            deferred_interest += day_count.accrued_interest(
+                balance,
+                rate.unwrap_or(period.annual_rate),
+                period.start,
+                period.end,
+            );
+            installments.push(Installment {
+                insurance: installment.insurance,
+                commission: installment.commission,
+                ..Installment::new(installment.date, 0.0, 0.0)
+            });
+        });
+
+    let annual_rate = rate.unwrap_or(periods[first].annual_rate);
+    let remaining = match arguments.keep_term {
+        // the same installments as before, each moved later by the length of the holiday
+        false => schedule.installments[first..]
+            .iter()
+            .map(|installment| Installment {
+                date: installment
+                    .date
+                    .checked_add_months(Months::new(count as u32))
+                    .unwrap_or(installment.date),
+                ..installment.clone()
+            })
+            .collect::<Vec<_>>(),
+        // the whole balance is repaid over the installments left after the holiday
+        true => {
+            let rest = &schedule.installments[first + count..];
+            let first_date = rest[0].date;
+            annuity_schedule(balance, annual_rate, rest.len() as u32, first_date)
+                .installments
+                .into_iter()
+                .zip(rest)
+                .map(|(installment, original)| Installment {
+                    insurance: original.insurance,
+                    commission: original.commission,
+                    ..installment
+                })
+                .collect()
+        }
+    };
+    let capitalized = match arguments.capitalization {
+        Capitalization::Added => annuity_schedule(
+            deferred_interest,
+            annual_rate,
+            remaining.len() as u32,
+            remaining[0].date,
+        )
+        .installments
+        .into_iter()
+        .map(|installment| (installment.principal, installment.interest))
+        .collect::<Vec<_>>(),
+        Capitalization::Spread => {
+            vec![(deferred_interest / remaining.len() as f64, 0.0); remaining.len()]
+        }
+    };
+    installments.extend(remaining.into_iter().zip(capitalized).map(
+        |(installment, (principal, interest))| Installment {
+            principal: installment.principal + principal,
+            interest: installment.interest + interest,
+            balance: None,
+            ..installment
+        },
+    ));
+
+    Ok(Schedule::new(installments))
+}
+
+pub(crate) fn print_deferral_comparison(
+    schedule: &Schedule,
+    arguments: &DeferArguments,
+    day_count: DayCount,
+) {
+    let currency = schedule.currency();
+    let deferred = match deferred_schedule(schedule, arguments, day_count) {
+        Ok(deferred) => deferred,
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    };
+
+    let first = schedule
+        .installments
+        .iter()
+        .position(|installment| installment.date >= arguments.from)
+        .unwrap_or(0);
+    // fewer installments than asked are deferred when the schedule ends before
+    let count = arguments.count.min(schedule.installments.len() - first);
+    let months = schedule.installments.len().max(deferred.installments.len());
+    let mut cumulative_difference = 0.0;
+    let rows = (first..months)
+        .map(|index| {
+            let original = schedule.installments.get(index);
+            let new = deferred.installments.get(index);
+            let original_total = original.map(Installment::total).unwrap_or(0.0);
+            let new_total = new.map(Installment::total).unwrap_or(0.0);
+            cumulative_difference += new_total - original_total;
+            vec![
+                (index + 1).to_string(),
+                new.or(original)
+                    .map(|installment| installment.date.to_string())
+                    .unwrap_or_default(),
+                format!("{:.2} {}", original_total, currency),
+                format!("{:.2} {}", new_total, currency),
+                format!("{:.2} {}", new_total - original_total, currency),
+                format!("{:.2} {}", cumulative_difference, currency),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    println!();
+    println!(
+        "Amânarea a {} rate începând cu {} ({}), dobândă calculată zilnic ({})",
+        count,
+        arguments.from,
+        arguments.capitalization.name(),
+        day_count.name()
+    );
+    print_table(
+        &[
+            "Număr",
+            "Dată",
+            "Rată inițială",
+            "Rată nouă",
+            "Diferență",
+            "Diferență cumulată",
+        ],
+        &rows,
+    );
+
+    let total_paid = |schedule: &Schedule| {
+        schedule
+            .installments
+            .iter()
+            .map(Installment::total)
+            .sum::<f64>()
+    };
+    let last_date = |schedule: &Schedule| {
+        schedule
+            .installments
+            .last()
+            .map(|installment| installment.date.to_string())
+            .unwrap_or_default()
+    };
+    print_table(
+        &["Scadențar", "Total plătit", "Ultima rată"],
+        &[
+            vec![
+                "Inițial".to_string(),
+                format!("{:.2} {}", total_paid(schedule), currency),
+                last_date(schedule),
+            ],
+            vec![
+                "Cu amânare".to_string(),
+                format!("{:.2} {}", total_paid(&deferred), currency),
+                last_date(&deferred),
+            ],
+        ],
+    );
+    println!(
+        "Costul suplimentar al amânării: {:.2} {}",
+        total_paid(&deferred) - total_paid(schedule),
+        currency
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn arguments(count: usize, capitalization: Capitalization) -> DeferArguments {
+        DeferArguments {
+            from: date(2024, 3, 1),
+            count,
+            capitalization,
+            keep_term: false,
+            rate: Some(6.0),
+        }
+    }
+
+    // 12 installments due on the 15th of every month of 2024
+    fn schedule() -> Schedule {
+        annuity_schedule(100000.0, 0.06, 12, date(2024, 1, 15))
+    }
+
+    fn total_paid(schedule: &Schedule) -> f64 {
+        schedule.installments.iter().map(Installment::total).sum()
+    }
+
+    #[test]
+    fn accrues_the_deferred_interest_with_the_day_count() {
+        let schedule = schedule();
+        let balance = schedule.balances_before()[2];
+        // spread without interest, the extra cost is the deferred interest
+        let cost = |day_count| {
+            let deferred =
+                deferred_schedule(&schedule, &arguments(2, Capitalization::Spread), day_count)
+                    .unwrap();
+            total_paid(&deferred) - total_paid(&schedule)
+        };
+        assert!((cost(DayCount::Thirty360) - balance * 0.06 * 60.0 / 360.0).abs() < 1e-6);
+        // from February 15 to April 15, 2024
+        assert!((cost(DayCount::Actual365) - balance * 0.06 * 60.0 / 365.0).abs() < 1e-6);
+        assert!((cost(DayCount::Actual360) - balance * 0.06 * 60.0 / 360.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn defers_only_the_installments_left() {
+        let schedule = schedule();
+        let deferred = deferred_schedule(
+            &schedule,
+            &DeferArguments {
+                from: date(2024, 10, 1),
+                ..arguments(5, Capitalization::Added)
+            },
+            DayCount::Actual365,
+        )
+        .unwrap();
+        // 9 installments paid, 3 deferred and the same 3 moved after the holiday
+        assert_eq!(deferred.installments.len(), 15);
+        assert_eq!(
+            deferred.installments[9..12]
+                .iter()
+                .map(|installment| (installment.date, installment.total()))
+                .collect::<Vec<_>>(),
+            [
+                (date(2024, 10, 15), 0.0),
+                (date(2024, 11, 15), 0.0),
+                (date(2024, 12, 15), 0.0),
+            ]
+        );
+        assert_eq!(deferred.installments[12].date, date(2025, 1, 15));
+
+        let error = deferred_schedule(
+            &schedule,
+            &DeferArguments {
+                from: date(2024, 10, 1),
+                keep_term: true,
+                ..arguments(5, Capitalization::Added)
+            },
+            DayCount::Actual365,
+        );
+        assert_eq!(
+            error.err().as_deref(),
+            Some("Cu --keep-term, după cele 3 rate amânate trebuie să rămână cel puțin o rată.")
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::arguments::HistoryArguments;
+use crate::schedule::{Installment, Schedule};
+use crate::schedulejson::{self, ScheduleDocument};
+use crate::table::print_table;
+
+const EVENTS_FILE: &str = "events.json";
+const REVISION_PREFIX: &str = "revision-";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum EventKind {
+    Prepayment,
+    Payment,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Event {
+    kind: EventKind,
+    date: NaiveDate,
+    amount: f64,
+}
+
+// every schedule revision imported for a loan, oldest first, with the prepayments and payments
+// made; kept as one directory per loan, in the data directory of the user
+struct LoanHistory {
+    directory: PathBuf,
+    revisions: Vec<ScheduleDocument>,
+    events: Vec<Event>,
+}
+
+// the history belongs to the user, not to the installed program: %APPDATA% on Windows,
+// $XDG_DATA_HOME or ~/.local/share elsewhere
+fn history_directory() -> Option<PathBuf> {
+    let data_directory = match cfg!(windows) {
+        true => std::env::var_os("APPDATA").map(PathBuf::from),
+        false => std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|directory| directory.is_absolute())
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            }),
+    }?;
+    Some(data_directory.join(env!("CARGO_PKG_NAME")).join("history"))
+}
+
+// the name given with --loan, otherwise the contract number, otherwise the name of the file;
+// the characters not allowed in file names are replaced
+fn loan_name(arguments: &HistoryArguments, schedule: &Schedule, input_file: &Path) -> String {
+    arguments
+        .loan
+        .clone()
+        .or_else(|| schedule.loan.contract.clone())
+        .or_else(|| {
+            input_file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .unwrap_or_default()
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+            true => c,
+            false => '-',
+        })
+        .collect()
+}
+
+fn read_events(events_file: &Path) -> Result<Vec<Event>, String> {
+    if !events_file.exists() {
+        return Ok(Vec::new());
+    }
+    let json = std::fs::read_to_string(events_file)
+        .map_err(|error| format!("Fișierul JSON nu a putut fi citit : {}", error))?;
+    serde_json::from_str(&json).map_err(|error| {
+        format!(
+            "Fișierul {} nu este valid : {}",
+            events_file.display(),
+            error
+        )
+    })
+}
+
+impl LoanHistory {
+    fn load(directory: PathBuf) -> Result<LoanHistory, String> {
+        let mut revision_files = match std::fs::read_dir(&directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| {
+                            name.starts_with(REVISION_PREFIX) && name.ends_with(".json")
+                        })
+                })
+                .collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        // the numbers are padded, so the names sort in the order the revisions were recorded
+        revision_files.sort();
+        let revisions = revision_files
+            .iter()
+            .map(schedulejson::read_document)
+            .collect::<Result<Vec<_>, _>>()?;
+        let events = read_events(&directory.join(EVENTS_FILE))?;
+        Ok(LoanHistory {
+            directory,
+            revisions,
+            events,
+        })
+    }
+
+    fn create_directory(&self) -> Result<(), String> {
+        std::fs::create_dir_all(&self.directory).map_err(|error| {
+            format!(
+                "Directorul {} nu a putut fi creat : {}",
+                self.directory.display(),
+                error
+            )
+        })
+    }
+
+    // a schedule is recorded only when it differs from the latest revision
+    fn record_revision(&mut self, document: ScheduleDocument) -> Result<bool, String> {
+        let unchanged = self.revisions.last().is_some_and(|latest| {
+            schedulejson::to_schedule(latest).installments
+                == schedulejson::to_schedule(&document).installments
+        });
+        if unchanged {
+            return Ok(false);
+        }
+        self.create_directory()?;
+        let revision_file = self.directory.join(format!(
+            "{}{:03}.json",
+            REVISION_PREFIX,
+            self.revisions.len() + 1
+        ));
+        schedulejson::write_document(&document, &revision_file)?;
+        self.revisions.push(document);
+        Ok(true)
+    }
+
+    // the same payment given again, for example when the command is run twice, is recorded once,
+    // unless `force` says that it is another payment of the same amount
+    fn record_event(
+        &mut self,
+        kind: EventKind,
+        date: NaiveDate,
+        amount: f64,
+        force: bool,
+    ) -> Result<bool, String> {
+        let recorded = self
+            .events
+            .iter()
+            .any(|event| event.kind == kind && event.date == date && event.amount == amount);
+        if recorded && !force {
+            return Ok(false);
+        }
+        self.events.push(Event { kind, date, amount });
+        self.events.sort_by_key(|event| event.date);
+        self.create_directory()?;
+        let events_file = self.directory.join(EVENTS_FILE);
+        let json = serde_json::to_string_pretty(&self.events).map_err(|error| {
+            format!("Evenimentele nu au putut fi convertite în JSON : {}", error)
+        })?;
+        std::fs::write(&events_file, json).map_err(|error| {
+            format!(
+                "Fișierul {} nu a putut fi scris : {}",
+                events_file.display(),
+                error
+            )
+        })?;
+        Ok(true)
+    }
+
+    // the currency of the revision in effect on `date`, the one of the first revision before it
+    fn currency_on(&self, date: NaiveDate) -> &str {
+        self.revisions
+            .iter()
+            .rev()
+            .find(|revision| {
+                revision
+                    .installments
+                    .first()
+                    .is_some_and(|installment| installment.date <= date)
+            })
+            .or(self.revisions.first())
+            .map(|revision| revision.currency.as_str())
+            .unwrap_or_default()
+    }
+
+    // the installments the loan actually had: every revision applies until the month of the first
+    // installment of the next one
+    fn effective_installments(&self) -> Vec<Installment> {
+        let month = |installment: &Installment| (installment.date.year(), installment.date.month());
+        let schedules = self
+            .revisions
+            .iter()
+            .map(schedulejson::to_schedule)
+            .collect::<Vec<_>>();
+        schedules
+            .iter()
+            .enumerate()
+            .flat_map(|(index, schedule)| {
+                let end = schedules
+                    .get(index + 1)
+                    .and_then(|next| next.installments.first())
+                    .map(month);
+                schedule
+                    .installments
+                    .iter()
+                    .filter(move |installment| end.is_none_or(|end| month(installment) < end))
+                    .cloned()
+            })
+            .collect()
+    }
+}
+
+fn print_timeline(history: &LoanHistory) {
+    let mut rows = history
+        .revisions
+        .iter()
+        .enumerate()
+        .map(|(index, revision)| {
+            // a revision takes effect from its first installment
+            let date = revision
+                .loan
+                .first_due_date
+                .or(revision.provenance.created)
+                .unwrap_or_default();
+            let details = format!(
+                "{} rate, capital {:.2} {}, ultima rată {}{}",
+                revision.installments.len(),
+                revision.loan.amount.unwrap_or(0.0),
+                revision.currency,
+                revision
+                    .loan
+                    .last_due_date
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+                revision
+                    .provenance
+                    .source
+                    .as_ref()
+                    .map(|source| format!(" ({})", source))
+                    .unwrap_or_default()
+            );
+            (date, format!("Scadențarul {}", index + 1), details)
+        })
+        .chain(history.events.iter().map(|event| {
+            let name = match event.kind {
+                EventKind::Prepayment => "Rambursare anticipată",
+                EventKind::Payment => "Plată",
+            };
+            (
+                event.date,
+                name.to_string(),
+                format!("{:.2} {}", event.amount, history.currency_on(event.date)),
+            )
+        }))
+        .collect::<Vec<_>>();
+    rows.sort_by_key(|(date, _, _)| *date);
+    print_table(
+        &["Dată", "Eveniment", "Detalii"],
+        &rows
+            .into_iter()
+            .map(|(date, event, details)| vec![date.to_string(), event, details])
+            .collect::<Vec<_>>(),
+    );
+}
+
+fn print_report(history: &LoanHistory) {
+    let Some(original) = history.revisions.first().map(schedulejson::to_schedule) else {
+        return;
+    };
+    let currency = history
+        .revisions
+        .last()
+        .map(|revision| revision.currency.as_str())
+        .unwrap_or_default();
+    println!();
+    print_timeline(history);
+
+    let installments = history.effective_installments();
+    let interest = |installments: &[Installment]| -> f64 {
+        installments
+            .iter()
+            .map(|installment| installment.interest)
+            .sum()
+    };
+    let original_interest = interest(&original.installments);
+    let actual_interest = interest(&installments);
+    let prepaid = history
+        .events
+        .iter()
+        .filter(|event| event.kind == EventKind::Prepayment)
+        .fold(0.0, |prepaid, event| prepaid + event.amount);
+
+    println!();
+    println!(
+        "Dobânda din scadențarul inițial: {:.2} {}",
+        original_interest, currency
+    );
+    println!(
+        "Dobânda plătită și rămasă după ultimul scadențar: {:.2} {}",
+        actual_interest, currency
+    );
+    println!(
+        "Dobânda economisită: {:.2} {} (rambursări anticipate de {:.2} {})",
+        original_interest - actual_interest,
+        currency,
+        prepaid,
+        currency
+    );
+
+    let today = chrono::Local::now().date_naive();
+    let remaining = installments
+        .iter()
+        .filter(|installment| installment.date > today)
+        .collect::<Vec<_>>();
+    println!();
+    println!(
+        "Capital rămas: {:.2} {}, în {} rate",
+        remaining
+            .iter()
+            .fold(0.0, |principal, installment| principal
+                + installment.principal),
+        currency,
+        remaining.len()
+    );
+    if let Some(next) = remaining.first() {
+        println!(
+            "Următoarea rată: {:.2} {}, la {}",
+            next.total(),
+            currency,
+            next.date
+        );
+    }
+    if let Some(last) = remaining.last() {
+        println!("Ultima rată: {}", last.date);
+    }
+    let payments = history
+        .events
+        .iter()
+        .filter(|event| event.kind == EventKind::Payment)
+        .map(|event| event.amount)
+        .collect::<Vec<_>>();
+    if !payments.is_empty() {
+        let due = installments
+            .iter()
+            .filter(|installment| installment.date <= today)
+            .map(Installment::total)
+            .sum::<f64>();
+        let paid = payments.iter().sum::<f64>();
+        println!(
+            "Plăți înregistrate: {:.2} {} din {:.2} {} scadenți (diferență {:.2} {})",
+            paid,
+            currency,
+            due,
+            currency,
+            paid - due,
+            currency
+        );
+    }
+}
+
+// records the schedule and the given payments in the history of the loan, then reports on it
+pub(crate) fn process_history(
+    schedule: &Schedule,
+    input_file: &Path,
+    bank: Option<String>,
+    arguments: &HistoryArguments,
+) {
+    let Some(directory) = history_directory() else {
+        eprintln!("Directorul de date al utilizatorului nu a putut fi găsit.");
+        return;
+    };
+    let name = loan_name(arguments, schedule, input_file);
+    let mut history = match LoanHistory::load(directory.join(&name)) {
+        Ok(history) => history,
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    };
+
+    let document = schedulejson::to_document(schedule, Some(input_file), bank);
+    match history.record_revision(document) {
+        Ok(true) => println!(
+            "Scadențarul a fost adăugat în istoricul creditului {} (versiunea {}).",
+            name,
+            history.revisions.len()
+        ),
+        Ok(false) => {}
+        Err(error) => {
+            eprintln!("{}", error);
+            return;
+        }
+    }
+    let date = arguments
+        .on
+        .unwrap_or_else(|| chrono::Local::now().date_naive());
+    let events = [
+        (EventKind::Prepayment, arguments.prepaid),
+        (EventKind::Payment, arguments.paid),
+    ];
+    for (kind, amount) in events {
+        let Some(amount) = amount else {
+            continue;
+        };
+        match history.record_event(kind, date, amount, arguments.force) {
+            Ok(true) => {}
+            Ok(false) => println!(
+                "Suma de {:.2} din {} este deja în istoricul creditului {} (cu --force este adăugată din nou).",
+                amount, date, name
+            ),
+            Err(error) => {
+                eprintln!("{}", error);
+                return;
+            }
+        }
+    }
+
+    print_report(&history);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_identical_payments_only_when_forced() {
+        let directory = std::env::temp_dir().join(format!(
+            "{}-history-{}",
+            env!("CARGO_PKG_NAME"),
+            std::process::id()
+        ));
+        let mut history = LoanHistory::load(directory.clone()).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        let mut record = |force| history.record_event(EventKind::Payment, date, 1500.0, force);
+        let recorded = [record(false), record(false), record(true)];
+        let events = LoanHistory::load(directory.clone()).unwrap().events.len();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(recorded, [Ok(true), Ok(false), Ok(true)]);
+        assert_eq!(events, 2);
+    }
+}
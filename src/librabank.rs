@@ -0,0 +1,87 @@
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::{footer_amounts, PrintedTotals};
+
+pub(crate) fn extract_payment_data(text: &str) -> Schedule {
+    let locale = Locale::detect(text);
+    // Data | Rata | Principal | Dobanda | Asigurare | Sold
+    let installments = text
+        .lines()
+        .filter_map(|line| {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let (date, length) = locale.date_prefix(&words)?;
+            let amounts = locale.amounts(&words[length..]);
+            if amounts.len() != 5 {
+                return None;
+            }
+
+            let principal = amounts[1];
+            let interest = amounts[2];
+            let insurance = amounts[3];
+            let balance = amounts[4];
+
+            Some(Installment {
+                insurance,
+                balance: Some(balance),
+                ..Installment::new(date, principal, interest)
+            })
+        })
+        .collect();
+
+    Schedule {
+        loan: LoanInfo::from_header(text, locale),
+        // the total row leaves the balance empty:
+        // Total | Rata | Principal | Dobanda | Asigurare
+        totals: footer_amounts(text, locale).map(|amounts| PrintedTotals {
+            principal: amounts.get(1).copied(),
+            interest: amounts.get(2).copied(),
+            insurance: amounts.get(3).copied(),
+            commission: None,
+        }),
+        ..Schedule::new(installments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/libra.txt");
+
+    #[test]
+    fn reads_the_installments_of_every_page() {
+        let schedule = extract_payment_data(FIXTURE);
+        assert_eq!(schedule.installments.len(), 3);
+
+        let first = &schedule.installments[0];
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2024, 3, 5).unwrap());
+        assert_eq!(first.principal, 1000.0);
+        assert_eq!(first.interest, 400.0);
+        assert_eq!(first.insurance, 30.0);
+        assert_eq!(first.balance, Some(79000.0));
+
+        let third = &schedule.installments[2];
+        assert_eq!(third.date, NaiveDate::from_ymd_opt(2024, 5, 5).unwrap());
+        assert_eq!(third.principal, 1010.03);
+        assert_eq!(third.interest, 389.97);
+        assert_eq!(third.balance, Some(76984.97));
+    }
+
+    #[test]
+    fn reads_the_total_row_and_the_header() {
+        let schedule = extract_payment_data(FIXTURE);
+        let totals = schedule.totals.unwrap();
+        assert_eq!(totals.principal, Some(3015.03));
+        assert_eq!(totals.interest, Some(1184.97));
+        assert_eq!(totals.insurance, Some(90.0));
+        assert_eq!(totals.commission, None);
+        assert_eq!(schedule.loan.contract.as_deref(), Some("LIB-2024-0815"));
+        assert_eq!(
+            schedule.loan.start_date,
+            NaiveDate::from_ymd_opt(2024, 2, 5)
+        );
+    }
+}
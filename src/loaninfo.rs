@@ -0,0 +1,236 @@
+use chrono::NaiveDate;
+
+use crate::locale::Locale;
+
+const CURRENCIES: [&str; 6] = ["RON", "LEI", "EUR", "USD", "CHF", "GBP"];
+const REFERENCE_INDEXES: [&str; 4] = ["IRCC", "ROBOR", "EURIBOR", "LIBOR"];
+
+// the labels are compared without case and diacritics
+const CONTRACT_LABELS: [&str; 5] = [
+    "nr. contract",
+    "numar contract",
+    "contract nr",
+    "contract de credit nr",
+    "contract number",
+];
+const AMOUNT_LABELS: [&str; 7] = [
+    "suma creditului",
+    "suma acordata",
+    "valoarea creditului",
+    "valoare credit",
+    "suma imprumutata",
+    "loan amount",
+    "credit amount",
+];
+const CURRENCY_LABELS: [&str; 3] = ["moneda", "valuta", "currency"];
+const RATE_LABELS: [&str; 5] = [
+    "rata dobanzii",
+    "dobanda anuala",
+    "dobanda curenta",
+    "rata anuala a dobanzii",
+    "interest rate",
+];
+const MARGIN_LABELS: [&str; 2] = ["marja", "margin"];
+const START_LABELS: [&str; 7] = [
+    "data acordarii",
+    "data tragerii",
+    "data contractului",
+    "data semnarii",
+    "data inceperii",
+    "data de start",
+    "start date",
+];
+const TERM_LABELS: [&str; 7] = [
+    "perioada creditului",
+    "perioada de creditare",
+    "durata creditului",
+    "numar de rate",
+    "numar rate",
+    "durata",
+    "loan term",
+];
+
+// what the schedule header says about the loan, every field is optional because banks print
+// different parts of it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct LoanInfo {
+    pub(crate) contract: Option<String>,
+    pub(crate) amount: Option<f64>,
+    pub(crate) currency: Option<String>,
+    // yearly percentage
+    pub(crate) annual_rate: Option<f64>,
+    // yearly percentage added to the reference index
+    pub(crate) margin: Option<f64>,
+    pub(crate) reference_index: Option<String>,
+    pub(crate) start_date: Option<NaiveDate>,
+    // number of monthly installments
+    pub(crate) term: Option<u32>,
+}
+
+// lowercase and without diacritics, with one character for every character of the text
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| match c.to_lowercase().next().unwrap_or(c) {
+            'ă' | 'â' => 'a',
+            'î' => 'i',
+            'ș' | 'ş' => 's',
+            'ț' | 'ţ' => 't',
+            c => c,
+        })
+        .collect()
+}
+
+// the words written after the first of the labels found on the line
+fn value_after<'a>(line: &'a str, labels: &[&str]) -> Option<Vec<&'a str>> {
+    let normalized = normalize(line);
+    let (position, label) = labels
+        .iter()
+        .find_map(|label| normalized.find(label).map(|position| (position, label)))?;
+    let start = normalized[..position].chars().count() + label.chars().count();
+    let byte_start = line
+        .char_indices()
+        .nth(start)
+        .map(|(index, _)| index)
+        .unwrap_or(line.len());
+    Some(
+        line[byte_start..]
+            .split(|c: char| c.is_whitespace() || c == ':')
+            .filter(|word| !word.is_empty())
+            .collect(),
+    )
+}
+
+fn currency(words: &[&str]) -> Option<String> {
+    words.iter().find_map(|word| {
+        let word = word
+            .trim_matches(|c: char| !c.is_alphabetic())
+            .to_uppercase();
+        match word.as_str() {
+            "LEI" => Some("RON".to_string()),
+            _ if CURRENCIES.contains(&word.as_str()) => Some(word),
+            _ => None,
+        }
+    })
+}
+
+fn reference_index(line: &str) -> Option<String> {
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    let position = words.iter().position(|word| {
+        REFERENCE_INDEXES.contains(
+            &word
+                .trim_matches(|c: char| !c.is_alphanumeric())
+                .to_uppercase()
+                .as_str(),
+        )
+    })?;
+    let name = words[position]
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_uppercase();
+    // the tenor, as in "ROBOR 3M" or "EURIBOR 6M"
+    match words.get(position + 1).map(|word| word.to_uppercase()) {
+        Some(tenor) if tenor.ends_with('M') && tenor[..tenor.len() - 1].parse::<u32>().is_ok() => {
+            Some(format!("{} {}", name, tenor))
+        }
+        _ => Some(name),
+    }
+}
+
+impl LoanInfo {
+    // the first value found after one of the usual labels, anywhere in the text
+    pub(crate) fn from_header(text: &str, locale: Locale) -> LoanInfo {
+        let mut info = LoanInfo::default();
+        text.lines().for_each(|line| {
+            if info.contract.is_none() {
+                info.contract = value_after(line, &CONTRACT_LABELS).and_then(|words| {
+                    words
+                        .into_iter()
+                        .find(|word| word.chars().any(|c| c.is_ascii_digit()))
+                        .map(str::to_string)
+                });
+            }
+            if info.amount.is_none() {
+                if let Some(words) = value_after(line, &AMOUNT_LABELS) {
+                    info.amount = locale.amounts(&words).first().copied();
+                    info.currency = info.currency.take().or_else(|| currency(&words));
+                }
+            }
+            if info.currency.is_none() {
+                info.currency =
+                    value_after(line, &CURRENCY_LABELS).and_then(|words| currency(&words));
+            }
+            // the annual percentage rate (DAE) is not the interest rate
+            let normalized = normalize(line);
+            if info.annual_rate.is_none()
+                && !normalized.contains("dae")
+                && !normalized.contains("efectiv")
+            {
+                info.annual_rate = value_after(line, &RATE_LABELS)
+                    .and_then(|words| locale.amounts(&words).first().copied());
+            }
+            if info.margin.is_none() {
+                info.margin = value_after(line, &MARGIN_LABELS)
+                    .and_then(|words| locale.amounts(&words).first().copied());
+            }
+            if info.reference_index.is_none() {
+                info.reference_index = reference_index(line);
+            }
+            if info.start_date.is_none() {
+                info.start_date = value_after(line, &START_LABELS).and_then(|words| {
+                    (0..words.len())
+                        .find_map(|start| locale.date_prefix(&words[start..]))
+                        .map(|(date, _)| date)
+                });
+            }
+            if info.term.is_none() {
+                info.term = value_after(line, &TERM_LABELS).and_then(|words| {
+                    let position = words.iter().position(|word| word.parse::<u32>().is_ok())?;
+                    let count = words[position].parse::<u32>().ok()?;
+                    match words.get(position + 1).map(|word| normalize(word)) {
+                        Some(unit) if unit.starts_with("an") || unit.starts_with("year") => {
+                            Some(count * 12)
+                        }
+                        _ => Some(count),
+                    }
+                });
+            }
+        });
+        info
+    }
+
+    // the currency alone says nothing about the loan
+    pub(crate) fn is_empty(&self) -> bool {
+        LoanInfo {
+            currency: None,
+            ..self.clone()
+        } == LoanInfo::default()
+    }
+}
+
+pub(crate) fn print_loan_info(loan: &LoanInfo) {
+    if loan.is_empty() {
+        return;
+    }
+    let currency = loan.currency.as_deref().unwrap_or("RON");
+    println!();
+    if let Some(contract) = &loan.contract {
+        println!("Contract: {}", contract);
+    }
+    if let Some(amount) = loan.amount {
+        println!("Suma creditului: {:.2} {}", amount, currency);
+    }
+    if let Some(annual_rate) = loan.annual_rate {
+        println!("Dobânda anuală: {:.2}%", annual_rate);
+    }
+    match (&loan.reference_index, loan.margin) {
+        (Some(index), Some(margin)) => println!("Dobânda variabilă: {} + {:.2}%", index, margin),
+        (Some(index), None) => println!("Indice de referință: {}", index),
+        (None, Some(margin)) => println!("Marja: {:.2}%", margin),
+        (None, None) => {}
+    }
+    if let Some(start_date) = loan.start_date {
+        println!("Data acordării: {}", start_date);
+    }
+    if let Some(term) = loan.term {
+        println!("Perioada: {} luni", term);
+    }
+}
@@ -0,0 +1,313 @@
+use chrono::NaiveDate;
+
+// romanian and english month names, abbreviations are matched by prefix ("iul", "sept.", "Jul")
+const MONTHS: [(&str, &str); 12] = [
+    ("ianuarie", "january"),
+    ("februarie", "february"),
+    ("martie", "march"),
+    ("aprilie", "april"),
+    ("mai", "may"),
+    ("iunie", "june"),
+    ("iulie", "july"),
+    ("august", "august"),
+    ("septembrie", "september"),
+    ("octombrie", "october"),
+    ("noiembrie", "november"),
+    ("decembrie", "december"),
+];
+
+// how amounts and dates are written in a document: "1.234,56" or "1,234.56", 15.07.2023 or 07/15/2023
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Locale {
+    pub(crate) decimal_separator: char,
+    pub(crate) day_first: bool,
+    // thousands are grouped with spaces, as in "1 234,56", so an amount can span several words
+    pub(crate) space_grouping: bool,
+}
+
+fn without_diacritics(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'ă' | 'â' => 'a',
+            'î' => 'i',
+            'ș' | 'ş' => 's',
+            'ț' | 'ţ' => 't',
+            _ => c,
+        })
+        .collect()
+}
+
+fn month(word: &str) -> Option<u32> {
+    let word = without_diacritics(word.trim_end_matches('.'));
+    if word.chars().count() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|(romanian, english)| romanian.starts_with(&word) || english.starts_with(&word))
+        .map(|index| index as u32 + 1)
+}
+
+fn year(word: &str) -> Option<i32> {
+    let year = word.parse::<i32>().ok()?;
+    match word.len() {
+        4 => Some(year),
+        2 => Some(2000 + year),
+        _ => None,
+    }
+}
+
+// the day, month and year of a date written with digits, in the order they are written
+fn numeric_date_parts(text: &str) -> Option<(u32, u32, &str)> {
+    let parts = text.split(['.', '/', '-']).collect::<Vec<_>>();
+    match parts[..] {
+        [first, second, third] => Some((first.parse().ok()?, second.parse().ok()?, third)),
+        _ => None,
+    }
+}
+
+impl Locale {
+    // the separators used by most amounts and dates in the text
+    pub(crate) fn detect(text: &str) -> Locale {
+        let (mut comma, mut point, mut day_first, mut month_first) = (0, 0, 0, 0);
+        let words = text
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_ascii_digit()))
+            .collect::<Vec<_>>();
+        words.iter().for_each(|word| {
+            if let Some((first, second, third)) = numeric_date_parts(word) {
+                if third.len() == 4 {
+                    match (first > 12, second > 12) {
+                        (true, false) => day_first += 1,
+                        (false, true) => month_first += 1,
+                        _ => {}
+                    }
+                    return;
+                }
+            }
+            if !word
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '.' || c == ',')
+            {
+                return;
+            }
+            // the last separator is the decimal one when it is followed by two digits, or when
+            // both separators appear
+            let Some(position) = word.rfind(['.', ',']) else {
+                return;
+            };
+            let separator = word[position..].chars().next().unwrap_or('.');
+            let both = word.contains('.') && word.contains(',');
+            if both || word.len() - position - 1 == 2 {
+                match separator {
+                    ',' => comma += 1,
+                    _ => point += 1,
+                }
+            }
+        });
+        let decimal_separator = if comma > point { ',' } else { '.' };
+        // the digits before the decimals of an amount such as "1.234,56"
+        let integer_digits = |word: &str| {
+            let (integer, decimals) = word.rsplit_once(decimal_separator)?;
+            let digits = integer.chars().filter(|c| c.is_ascii_digit()).count();
+            (decimals.len() == 2
+                && decimals.chars().all(|c| c.is_ascii_digit())
+                && integer
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c == '.' || c == ','))
+            .then_some(digits)
+        };
+        let is_digits = |word: &str| !word.is_empty() && word.chars().all(|c| c.is_ascii_digit());
+        // "1 234,56": one to three digits followed by three digits with decimals
+        let space_grouped = words.windows(2).any(|pair| {
+            (1..=3).contains(&pair[0].len())
+                && is_digits(pair[0])
+                && pair[1].get(..3).is_some_and(is_digits)
+                && integer_digits(pair[1]) == Some(3)
+        });
+        // an amount such as "1234,56" or "1.234,56" shows that thousands are not grouped with
+        // spaces
+        let written_whole = words
+            .iter()
+            .any(|word| integer_digits(word).is_some_and(|digits| digits >= 4));
+        Locale {
+            decimal_separator,
+            day_first: day_first >= month_first,
+            space_grouping: space_grouped && !written_whole,
+        }
+    }
+
+    // spaces, non-breaking spaces and thousands separators are ignored
+    pub(crate) fn parse_amount(&self, word: &str) -> Option<f64> {
+        let value = word
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == self.decimal_separator || *c == '-')
+            .map(|c| match c == self.decimal_separator {
+                true => '.',
+                false => c,
+            })
+            .collect::<String>();
+        if !value.chars().any(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        value.parse().ok()
+    }
+
+    // "15.07.2023", "15/07/2023", "15-07-2023", "2023-07-15", "15 iul 2023", "15-Jul-2023", "15 iulie 2023"
+    pub(crate) fn parse_date(&self, text: &str) -> Option<NaiveDate> {
+        let text = text.trim();
+        if let Some((first, second, third)) = numeric_date_parts(text) {
+            if text.split(['.', '/', '-']).next()?.len() == 4 {
+                let year = text[..4].parse().ok()?;
+                return NaiveDate::from_ymd_opt(year, second, third.parse().ok()?);
+            }
+            let (day, month) = match self.day_first {
+                true => (first, second),
+                false => (second, first),
+            };
+            return NaiveDate::from_ymd_opt(year(third)?, month, day);
+        }
+        let parts = text
+            .split([' ', '-', '.'])
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>();
+        match parts[..] {
+            [day, name, year_word] => {
+                NaiveDate::from_ymd_opt(year(year_word)?, month(name)?, day.parse().ok()?)
+            }
+            _ => None,
+        }
+    }
+
+    // a date written at the start of `words`, with the number of words it takes
+    pub(crate) fn date_prefix(&self, words: &[&str]) -> Option<(NaiveDate, usize)> {
+        if let Some(date) = words.first().and_then(|word| self.parse_date(word)) {
+            return Some((date, 1));
+        }
+        let date = self.parse_date(&words.get(..3)?.join(" "))?;
+        Some((date, 3))
+    }
+
+    // the amounts in `words`; when thousands are grouped with spaces, a word of three digits
+    // continues an amount of one to three digits, or one already grouped, as in "1 234 567,89"
+    pub(crate) fn amounts(&self, words: &[&str]) -> Vec<f64> {
+        let is_digits = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+        let is_grouped_integer = |amount: &str| {
+            let mut groups = amount.split(' ');
+            groups.next().is_some_and(|first| {
+                (1..=3).contains(&first.len()) && is_digits(first) && !first.starts_with('0')
+            }) && groups.all(|group| group.len() == 3 && is_digits(group))
+        };
+        let mut amounts: Vec<String> = Vec::new();
+        words.iter().for_each(|word| {
+            let is_group = word.get(..3).is_some_and(is_digits)
+                && word.get(3..).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with(self.decimal_separator)
+                });
+            match amounts.last_mut() {
+                Some(amount) if self.space_grouping && is_group && is_grouped_integer(amount) => {
+                    amount.push(' ');
+                    amount.push_str(word);
+                }
+                _ => amounts.push(word.to_string()),
+            }
+        });
+        amounts
+            .iter()
+            .filter_map(|amount| self.parse_amount(amount))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROMANIAN: Locale = Locale {
+        decimal_separator: ',',
+        day_first: true,
+        space_grouping: false,
+    };
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn detects_the_separators_of_the_document() {
+        let locale = Locale::detect("15.07.2023 1.234,56 780,25 20.08.2023");
+        assert_eq!(locale.decimal_separator, ',');
+        assert!(locale.day_first);
+        assert!(!locale.space_grouping);
+
+        let locale = Locale::detect("07/15/2023 1,234.56 780.25 08/20/2023");
+        assert_eq!(locale.decimal_separator, '.');
+        assert!(!locale.day_first);
+
+        let locale = Locale::detect("15.07.2023 1 234,56 780,25");
+        assert_eq!(locale.decimal_separator, ',');
+        assert!(locale.space_grouping);
+
+        // no thousands separators at all
+        let locale = Locale::detect("15.07.2023 780,25 23,15 803,40");
+        assert!(!locale.space_grouping);
+    }
+
+    #[test]
+    fn keeps_a_small_number_before_an_amount_apart() {
+        // the installment number is followed by the principal, the balance is written whole
+        let text = "31 15.07.2023 103,17 781,25 98792,79\n32 15.08.2023 104,02 780,40 98688,77";
+        let locale = Locale::detect(text);
+        assert!(!locale.space_grouping);
+        let words = ["31", "103,17", "781,25", "98792,79"];
+        assert_eq!(locale.amounts(&words), [31.0, 103.17, 781.25, 98792.79]);
+
+        // a number of one or two digits is not a group of thousands
+        let text = "31 15.07.2023 98 792,79 781,25";
+        let locale = Locale::detect(text);
+        assert!(locale.space_grouping);
+        let words = ["31", "98", "792,79", "781,25"];
+        assert_eq!(locale.amounts(&words), [31.0, 98792.79, 781.25]);
+    }
+
+    #[test]
+    fn parses_amounts_without_the_thousands_separators() {
+        assert_eq!(ROMANIAN.parse_amount("1.234,56"), Some(1234.56));
+        assert_eq!(ROMANIAN.parse_amount("-780,25"), Some(-780.25));
+        assert_eq!(ROMANIAN.parse_amount("1 234,56 RON"), Some(1234.56));
+        assert_eq!(ROMANIAN.parse_amount("RON"), None);
+        let english = Locale {
+            decimal_separator: '.',
+            ..ROMANIAN
+        };
+        assert_eq!(english.parse_amount("1,234.56"), Some(1234.56));
+    }
+
+    #[test]
+    fn reads_dates_of_one_or_three_words() {
+        let words = ["15.07.2023", "1.234,56"];
+        assert_eq!(ROMANIAN.date_prefix(&words), Some((date(2023, 7, 15), 1)));
+        let words = ["15", "iul", "2023", "1.234,56"];
+        assert_eq!(ROMANIAN.date_prefix(&words), Some((date(2023, 7, 15), 3)));
+        let words = ["15-Jul-2023"];
+        assert_eq!(ROMANIAN.date_prefix(&words), Some((date(2023, 7, 15), 1)));
+        assert_eq!(ROMANIAN.date_prefix(&["Total", "1.234,56"]), None);
+    }
+
+    #[test]
+    fn joins_thousands_groups_only_when_spaces_group_thousands() {
+        let words = ["15", "250,00", "1.234,56"];
+        assert_eq!(ROMANIAN.amounts(&words), [15.0, 250.0, 1234.56]);
+
+        let spaced = Locale {
+            space_grouping: true,
+            ..ROMANIAN
+        };
+        let words = ["1", "234", "567,89", "780,25", "12", "345,00"];
+        assert_eq!(spaced.amounts(&words), [1234567.89, 780.25, 12345.0]);
+        // a group never continues an amount with decimals or of more than three digits
+        let words = ["780,25", "125,00", "1250", "300,00"];
+        assert_eq!(spaced.amounts(&words), [780.25, 125.0, 1250.0, 300.0]);
+    }
+}
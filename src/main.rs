@@ -1,78 +1,354 @@
-use arguments::Arguments;
-use chrono::NaiveDate;
+use arguments::{Arguments, Command};
+use bank::{Bank, Extractor, Parsers};
 use clap::Parser;
 use colored::Colorize;
+use prepayment::PrepaymentRules;
+use schedule::{PaymentData, Schedule};
 use std::cmp::max;
-use strum::{EnumIter, IntoEnumIterator};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
 use table::{dashed_line, table_header};
 
 mod alphabank;
 mod alphabankexported;
 mod arguments;
+mod bank;
+mod batch;
+mod bcrbank;
+mod brdbank;
+mod btbank;
+mod cecbank;
+mod csvimport;
+mod daycount;
+mod deferral;
+mod history;
 mod ingbank;
+mod librabank;
+mod loaninfo;
+mod locale;
+mod otpbank;
+mod pdfdocument;
+mod pdftable;
+mod plugin;
+mod portfolio;
+mod prepayment;
+mod profile;
+mod raiffeisenbank;
+mod reconcile;
+mod refinance;
+mod schedule;
+mod schedulediff;
+mod schedulejson;
+mod spreadsheetimport;
+mod statementimport;
 mod table;
-
-#[derive(EnumIter, Debug)]
-enum Bank {
-    AlphaBankExported,
-    AlphaBank,
-    ING,
-}
-impl Bank {
-    fn determine_bank(file_name: &str) -> Option<Bank> {
-        let file_name = file_name.to_ascii_lowercase();
-        Bank::iter().find(|bank| file_name.starts_with(&format!("{:?}", bank).to_ascii_lowercase()))
-    }
-}
+mod totals;
+mod unicreditbank;
+mod watch;
 
 fn main() {
     let args = Arguments::parse();
+    let parsers = Parsers {
+        profiles: profile::load_profiles(),
+        plugins: plugin::load_plugins(),
+    };
+
+    if args.list_banks {
+        bank::print_banks(&parsers.profiles, &parsers.plugins);
+    }
+
+    if let Some(Command::Watch(arguments)) = &args.command {
+        if !args.input_files.is_empty() {
+            eprintln!("Directoarele urmărite se dau după comandă: watch <director>");
+            std::process::exit(2);
+        }
+        watch::watch(arguments);
+        return;
+    }
 
-    process_file(args);
+    // the subcommands make the input files optional for the command line, diff has its own
+    let input_files = match &args.command {
+        Some(Command::Diff(diff)) if args.input_files.is_empty() => vec![diff.old_file.clone()],
+        Some(Command::Diff(_)) => {
+            eprintln!("Scadențarele comparate se dau după comandă: diff <vechi> <nou>");
+            std::process::exit(2);
+        }
+        Some(_) if args.input_files.is_empty() => {
+            eprintln!("Niciun scadențar de analizat.");
+            std::process::exit(2);
+        }
+        _ => batch::expand_input_files(&args.input_files),
+    };
+    // the exit code tells scripts, and the watch command, that a file could not be read
+    let failed = match args.budget {
+        Some(budget) => !process_portfolio(&args, &parsers, &input_files, budget),
+        None if input_files.len() > 1 => {
+            let results = input_files
+                .iter()
+                .map(|input_file| {
+                    println!();
+                    println!("{}", input_file.display().to_string().bold());
+                    // a file that cannot be processed does not stop the others
+                    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        process_file(&args, &parsers, input_file)
+                    }))
+                    .unwrap_or(None);
+                    (input_file.clone(), result)
+                })
+                .collect::<Vec<_>>();
+            batch::print_batch_summary(&results);
+            results.iter().any(|(_, result)| result.is_none())
+        }
+        None => {
+            input_files
+                .iter()
+                .filter(|input_file| process_file(&args, &parsers, input_file).is_none())
+                .count()
+                > 0
+        }
+    };
 
-    press_btn_continue::wait("Apasati orice tasta pentru a inchide programul ...").unwrap();
+    if !args.non_interactive {
+        press_btn_continue::wait("Apasati orice tasta pentru a inchide programul ...").unwrap();
+    }
+    if failed {
+        std::process::exit(1);
+    }
 }
 
-fn process_file(args: Arguments) {
-    let Some(extension) = args.input_file.extension() else {
+fn load_schedule(
+    args: &Arguments,
+    parsers: &Parsers,
+    input_file: &PathBuf,
+) -> Option<(Option<Extractor>, Schedule)> {
+    let Some(extension) = input_file.extension() else {
         eprintln!("Fișierul nu are extensie.");
-        return;
+        return None;
     };
-    if extension != "pdf" {
-        eprintln!("Fișierul nu are extensie PDF.");
-        return;
+    let extension = extension.to_string_lossy().to_ascii_lowercase();
+    if extension == "json" {
+        return match schedulejson::read_document(input_file) {
+            Ok(document) => {
+                let bank = document
+                    .provenance
+                    .bank
+                    .as_ref()
+                    .and_then(|name| Bank::iter().find(|bank| bank.name() == *name))
+                    .map(Extractor::Bank);
+                Some((bank, schedulejson::to_schedule(&document)))
+            }
+            Err(error) => {
+                eprintln!("{}", error);
+                None
+            }
+        };
     }
-    let Some(file_name) = args.input_file.file_name() else {
+    let imported = match extension.as_str() {
+        "pdf" => None,
+        "csv" => Some(csvimport::import_schedule(input_file, &args.import)),
+        "xlsx" | "xls" | "ods" => {
+            Some(spreadsheetimport::import_schedule(input_file, &args.import))
+        }
+        _ => {
+            eprintln!("Fișierul nu are extensie PDF, CSV, XLSX, ODS sau JSON.");
+            return None;
+        }
+    };
+    match imported {
+        Some(Ok(schedule)) => return Some((None, schedule)),
+        Some(Err(error)) => {
+            eprintln!("{}", error);
+            return None;
+        }
+        None => {}
+    }
+    let Some(file_name) = input_file.file_name() else {
         eprintln!("Fișierul nu are nume.");
-        return;
+        return None;
     };
     let Some(file_name) = file_name.to_str() else {
         eprintln!("Fișierul nu are nume valid.");
-        return;
+        return None;
+    };
+    let document = match pdfdocument::open_document(
+        input_file,
+        args.password.as_deref(),
+        !args.non_interactive,
+    ) {
+        Ok(document) => document,
+        Err(error) => {
+            eprintln!("{}", error);
+            return None;
+        }
+    };
+    let text = match pdfdocument::extract_text(&document) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("{}", error);
+            return None;
+        }
     };
-    let Some(bank) = Bank::determine_bank(file_name) else {
+    let Parsers { profiles, plugins } = parsers;
+    let Some(extractor) = Extractor::determine_extractor(file_name, &text, profiles, plugins)
+    else {
         let banks = Bank::iter()
-            .map(|bank| format!("{:?}", bank).to_ascii_lowercase())
+            .map(|bank| bank.name())
+            .chain(
+                profiles
+                    .iter()
+                    .map(|profile| profile.name.to_ascii_lowercase()),
+            )
+            .chain(
+                plugins
+                    .iter()
+                    .map(|plugin| plugin.name.to_ascii_lowercase()),
+            )
             .collect::<Vec<String>>()
             .join(", ");
-        eprintln!("Numele fișierului nu corespunde niciunui tip de bancă. Numele fișierului trebuie să înceapă cu una dintre următoarele valori: {}", banks);
-        return;
-    };
-    let payment_data = match bank {
-        Bank::AlphaBankExported => alphabankexported::extract_payment_data(&args.input_file),
-        Bank::AlphaBank => alphabankexported::extract_payment_data(&args.input_file),
-        Bank::ING => ingbank::extract_payment_data(&args.input_file),
+        eprintln!("Banca nu a putut fi recunoscută din conținutul fișierului. Numele fișierului trebuie să înceapă cu una dintre următoarele valori: {}", banks);
+        return None;
     };
-    print_calculation_results(payment_data);
+    match extractor.extract_payment_data(input_file, &document, &text) {
+        Ok(schedule) => {
+            totals::check_totals(&schedule);
+            Some((Some(extractor), schedule))
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            None
+        }
+    }
+}
+
+// false when a schedule could not be read
+fn process_portfolio(
+    args: &Arguments,
+    parsers: &Parsers,
+    input_files: &[PathBuf],
+    budget: f64,
+) -> bool {
+    let loans = input_files
+        .iter()
+        .filter_map(|input_file| {
+            let (_, schedule) = load_schedule(args, parsers, input_file)?;
+            let name = input_file.file_stem()?.to_string_lossy().to_string();
+            Some((name, schedule))
+        })
+        .collect::<Vec<_>>();
+    if !loans.is_empty() {
+        if let Err(error) = portfolio::print_portfolio_results(&loans, budget) {
+            eprintln!("{}", error);
+            return false;
+        }
+    }
+    loans.len() == input_files.len()
+}
+
+// the report of one file, the schedule is returned for the summary of a batch
+fn process_file(
+    args: &Arguments,
+    parsers: &Parsers,
+    input_file: &PathBuf,
+) -> Option<(Option<String>, Schedule)> {
+    let (extractor, schedule) = load_schedule(args, parsers, input_file)?;
+    analyze_schedule(args, parsers, input_file, extractor.as_ref(), &schedule);
+    Some((extractor.as_ref().map(Extractor::name), schedule))
 }
 
-fn print_calculation_results(
-    payment_data: (
-        Vec<(NaiveDate, f64, f64, f64, f64, f64, f64)>,
-        Vec<usize>,
-        Vec<usize>,
-    ),
+fn analyze_schedule(
+    args: &Arguments,
+    parsers: &Parsers,
+    input_file: &Path,
+    extractor: Option<&Extractor>,
+    schedule: &Schedule,
 ) {
+    if args.export_json
+        && input_file
+            .extension()
+            .is_some_and(|extension| extension != "json")
+    {
+        let json_file = input_file.with_extension("json");
+        let document =
+            schedulejson::to_document(schedule, Some(input_file), extractor.map(Extractor::name));
+        match schedulejson::write_document(&document, &json_file) {
+            Ok(()) => println!("Scadențarul a fost salvat în {}", json_file.display()),
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+    match &args.command {
+        Some(Command::Compare(offer)) => {
+            refinance::print_refinance_comparison(schedule, offer);
+            return;
+        }
+        Some(Command::Defer(deferral)) => {
+            deferral::print_deferral_comparison(schedule, deferral, args.day_count);
+            return;
+        }
+        Some(Command::Diff(diff)) => {
+            if let Some((_, new_schedule)) = load_schedule(args, parsers, &diff.new_file) {
+                schedulediff::print_schedule_diff(schedule, &new_schedule);
+            }
+            return;
+        }
+        Some(Command::Reconcile(reconcile)) => {
+            match statementimport::import_statement(&reconcile.statement, &args.import, reconcile) {
+                Ok(transactions) => {
+                    reconcile::print_reconciliation(schedule, &transactions, reconcile)
+                }
+                Err(error) => eprintln!("{}", error),
+            }
+            return;
+        }
+        Some(Command::History(history)) => {
+            history::process_history(
+                schedule,
+                input_file,
+                extractor.map(Extractor::name),
+                history,
+            );
+            return;
+        }
+        // handled in main, before any file is read
+        Some(Command::Watch(_)) | None => {}
+    }
+
+    loaninfo::print_loan_info(&schedule.loan);
+    print_calculation_results(schedule.payment_data(), schedule.currency());
+
+    if let Some(amount) = args.prepayment {
+        prepayment::print_prepayment_results(schedule, amount, args.day_count);
+
+        if let (Some(window_start), Some(window_end)) = (args.window_start, args.window_end) {
+            if window_start > window_end {
+                eprintln!(
+                    "Intervalul de rambursare nu este valid: {} este după {}.",
+                    window_start, window_end
+                );
+                return;
+            }
+            let default_rules = extractor
+                .map(Extractor::prepayment_rules)
+                .unwrap_or_default();
+            let rules = PrepaymentRules {
+                only_on_due_dates: args
+                    .only_on_due_dates
+                    .unwrap_or(default_rules.only_on_due_dates),
+                notice_days: args.notice_days.unwrap_or(default_rules.notice_days),
+            };
+            prepayment::print_best_prepayment_dates(
+                schedule,
+                amount,
+                (window_start, window_end),
+                rules,
+                args.deposit_rate,
+                args.day_count,
+            );
+        }
+    }
+}
+
+fn print_calculation_results(payment_data: PaymentData, currency: &str) {
     let (payment_data, max_local_performance_indexes, max_global_performance_indexes) =
         payment_data;
 
@@ -83,7 +359,9 @@ fn print_calculation_results(
         payment_data
             .iter()
             .map(|(_, local_principal, _, _, _, _, _)| {
-                format!("{:.2} RON", local_principal).chars().count()
+                format!("{:.2} {}", local_principal, currency)
+                    .chars()
+                    .count()
             })
             .max()
             .unwrap_or(0),
@@ -93,7 +371,9 @@ fn print_calculation_results(
         payment_data
             .iter()
             .map(|(_, _, local_interest, _, _, _, _)| {
-                format!("{:.2} RON", local_interest).chars().count()
+                format!("{:.2} {}", local_interest, currency)
+                    .chars()
+                    .count()
             })
             .max()
             .unwrap_or(0),
@@ -113,7 +393,9 @@ fn print_calculation_results(
         payment_data
             .iter()
             .map(|(_, _, _, _, total_principal, _, _)| {
-                format!("{:.2} RON", total_principal).chars().count()
+                format!("{:.2} {}", total_principal, currency)
+                    .chars()
+                    .count()
             })
             .max()
             .unwrap_or(0),
@@ -123,7 +405,9 @@ fn print_calculation_results(
         payment_data
             .iter()
             .map(|(_, _, _, _, _, total_interest, _)| {
-                format!("{:.2} RON", total_interest).chars().count()
+                format!("{:.2} {}", total_interest, currency)
+                    .chars()
+                    .count()
             })
             .max()
             .unwrap_or(0),
@@ -143,7 +427,9 @@ fn print_calculation_results(
         payment_data
             .iter()
             .map(|(_, _, _, _, _, _, total_performance)| {
-                format!("{:.2} RON", total_performance).chars().count()
+                format!("{:.2} {}", total_performance, currency)
+                    .chars()
+                    .count()
             })
             .max()
             .unwrap_or(0),
@@ -184,12 +470,12 @@ fn print_calculation_results(
             line_strings.push(format!("{:width$}", date, width = date_column_width));
             line_strings.push(format!(
                 "{:>width$}",
-                format!("{:.2} RON", local_principal),
+                format!("{:.2} {}", local_principal, currency),
                 width = local_principal_column_width
             ));
             line_strings.push(format!(
                 "{:>width$}",
-                format!("{:.2} RON", local_interest),
+                format!("{:.2} {}", local_interest, currency),
                 width = local_interest_column_width
             ));
             let local_performance_string = format!(
@@ -222,12 +508,12 @@ fn print_calculation_results(
             ));
             line_strings.push(format!(
                 "{:>width$}",
-                format!("{:.2} RON", total_principal),
+                format!("{:.2} {}", total_principal, currency),
                 width = total_principal_column_width
             ));
             line_strings.push(format!(
                 "{:>width$}",
-                format!("{:.2} RON", total_interest),
+                format!("{:.2} {}", total_interest, currency),
                 width = total_interest_column_width
             ));
             let global_performance_string = format!(
@@ -260,7 +546,7 @@ fn print_calculation_results(
             ));
             line_strings.push(format!(
                 "{:>width$}",
-                format!("{:.2} RON", total_principal + total_interest),
+                format!("{:.2} {}", total_principal + total_interest, currency),
                 width = total_absolut_column_width
             ));
 
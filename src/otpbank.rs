@@ -0,0 +1,89 @@
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::{footer_amounts, PrintedTotals};
+
+pub(crate) fn extract_payment_data(text: &str) -> Schedule {
+    let locale = Locale::detect(text);
+    // Nr. | Data | Principal | Dobanda | Comision | Asigurare | Total | Sold
+    // the layout stayed the same after the merger with Banca Transilvania
+    let installments = text
+        .lines()
+        .filter_map(|line| {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            words.first()?.parse::<usize>().ok()?;
+            let (date, length) = locale.date_prefix(&words[1..])?;
+            let amounts = locale.amounts(&words[1 + length..]);
+            if amounts.len() != 6 {
+                return None;
+            }
+
+            let principal = amounts[0];
+            let interest = amounts[1];
+            let commission = amounts[2];
+            let insurance = amounts[3];
+            let balance = amounts[5];
+
+            Some(Installment {
+                insurance,
+                commission,
+                balance: Some(balance),
+                ..Installment::new(date, principal, interest)
+            })
+        })
+        .collect();
+
+    Schedule {
+        loan: LoanInfo::from_header(text, locale),
+        // the total row leaves the balance empty:
+        // Total | Principal | Dobanda | Comision | Asigurare | Total
+        totals: footer_amounts(text, locale).map(|amounts| PrintedTotals {
+            principal: amounts.first().copied(),
+            interest: amounts.get(1).copied(),
+            insurance: amounts.get(3).copied(),
+            commission: amounts.get(2).copied(),
+        }),
+        ..Schedule::new(installments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/otp.txt");
+
+    #[test]
+    fn reads_the_installments_of_every_page() {
+        let schedule = extract_payment_data(FIXTURE);
+        assert_eq!(schedule.installments.len(), 3);
+
+        let first = &schedule.installments[0];
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2024, 6, 10).unwrap());
+        assert_eq!(first.principal, 300.0);
+        assert_eq!(first.interest, 900.0);
+        assert_eq!(first.commission, 15.0);
+        assert_eq!(first.insurance, 40.0);
+        assert_eq!(first.balance, Some(199700.0));
+
+        let third = &schedule.installments[2];
+        assert_eq!(third.date, NaiveDate::from_ymd_opt(2024, 8, 10).unwrap());
+        assert_eq!(third.principal, 302.71);
+        assert_eq!(third.interest, 897.29);
+        assert_eq!(third.balance, Some(199095.94));
+    }
+
+    #[test]
+    fn reads_the_total_row_and_the_header() {
+        let schedule = extract_payment_data(FIXTURE);
+        let totals = schedule.totals.unwrap();
+        assert_eq!(totals.principal, Some(904.06));
+        assert_eq!(totals.interest, Some(2695.94));
+        assert_eq!(totals.commission, Some(45.0));
+        assert_eq!(totals.insurance, Some(120.0));
+        assert_eq!(schedule.loan.amount, Some(200000.0));
+        assert_eq!(schedule.loan.annual_rate, Some(5.4));
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::IsTerminal;
+use std::path::Path;
+
+use pdf_extract::{Document, PlainTextOutput};
+
+const PASSWORD_ATTEMPTS: usize = 3;
+// the password of the protected PDFs for scripts, besides --password, which shows in the process
+// list and the shell history
+const PASSWORD_VARIABLE: &str = "PDF_PASSWORD";
+
+// asks for the password in the console, without echo, until it opens the document
+fn prompt_password(
+    document: &mut Document,
+    pdf_file: &Path,
+    interactive: bool,
+) -> Result<bool, String> {
+    if !interactive || !std::io::stdin().is_terminal() {
+        return Err(format!(
+            "Fișierul {} este protejat cu parolă. Dați parola cu --password sau în variabila {}.",
+            pdf_file.display(),
+            PASSWORD_VARIABLE
+        ));
+    }
+    for _ in 0..PASSWORD_ATTEMPTS {
+        let password =
+            rpassword::prompt_password(format!("Parola fișierului {}: ", pdf_file.display()))
+                .map_err(|error| format!("Parola nu a putut fi citită : {}", error))?;
+        if document.decrypt(&password).is_ok() {
+            return Ok(true);
+        }
+        eprintln!("Parola nu este corectă.");
+    }
+    Ok(false)
+}
+
+// opens the PDF, asking for its password when it is protected and the one given with --password
+// or in the environment does not open it; the password is never printed
+pub(crate) fn open_document(
+    pdf_file: &Path,
+    password: Option<&str>,
+    interactive: bool,
+) -> Result<Document, String> {
+    let mut document = Document::load(pdf_file)
+        .map_err(|error| format!("Fișierul PDF nu a putut fi citit : {}", error))?;
+    if !document.is_encrypted() {
+        return Ok(document);
+    }
+
+    // documents protected only against changes open with an empty password
+    if document.decrypt("").is_err() {
+        let given = password
+            .map(str::to_string)
+            .or_else(|| std::env::var(PASSWORD_VARIABLE).ok());
+        let decrypted = match given {
+            Some(password) if document.decrypt(&password).is_ok() => true,
+            Some(_) => {
+                eprintln!("Parola dată pentru {} nu este corectă.", pdf_file.display());
+                prompt_password(&mut document, pdf_file, interactive)?
+            }
+            None => prompt_password(&mut document, pdf_file, interactive)?,
+        };
+        if !decrypted {
+            return Err(format!(
+                "Fișierul {} nu a putut fi deschis fără parola corectă.",
+                pdf_file.display()
+            ));
+        }
+    }
+
+    // the content is decrypted in place, without this the PDF would still look encrypted
+    document.trailer.remove(b"Encrypt");
+    Ok(document)
+}
+
+pub(crate) fn extract_text(document: &Document) -> Result<String, String> {
+    let mut text = String::new();
+    pdf_extract::output_doc(document, &mut PlainTextOutput::new(&mut text))
+        .map_err(|error| format!("Fișierul PDF nu a putut fi citit : {}", error))?;
+    Ok(text)
+}
@@ -0,0 +1,580 @@
+use pdf_extract::{Document, MediaBox, OutputDev, OutputError, Transform};
+
+use crate::csvimport::text_amount;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::PrintedTotals;
+
+// a character with its position on the page, y grows downwards
+struct Glyph {
+    page: u32,
+    x: f64,
+    y: f64,
+    width: f64,
+    size: f64,
+    text: String,
+}
+
+#[derive(Default)]
+struct GlyphCollector {
+    page: u32,
+    page_height: f64,
+    glyphs: Vec<Glyph>,
+}
+
+impl OutputDev for GlyphCollector {
+    fn begin_page(
+        &mut self,
+        page_num: u32,
+        media_box: &MediaBox,
+        _: Option<(f64, f64, f64, f64)>,
+    ) -> Result<(), OutputError> {
+        self.page = page_num;
+        self.page_height = media_box.ury - media_box.lly;
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn output_character(
+        &mut self,
+        trm: &Transform,
+        width: f64,
+        _spacing: f64,
+        font_size: f64,
+        char: &str,
+    ) -> Result<(), OutputError> {
+        // the side of the square with the same area as the scaled glyph box
+        let size = ((font_size * (trm.m11 + trm.m21)) * (font_size * (trm.m12 + trm.m22)))
+            .abs()
+            .sqrt();
+        self.glyphs.push(Glyph {
+            page: self.page,
+            x: trm.m31,
+            y: self.page_height - trm.m32,
+            width: width * size,
+            size,
+            text: char.to_string(),
+        });
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct Word {
+    left: f64,
+    right: f64,
+    text: String,
+}
+
+struct Line {
+    page: u32,
+    y: f64,
+    size: f64,
+    words: Vec<Word>,
+}
+
+// glyphs on the same baseline form a line, glyphs closer than a fifth of their size form a word
+fn lines(mut glyphs: Vec<Glyph>) -> Vec<Line> {
+    glyphs.sort_by(|a, b| {
+        a.page
+            .cmp(&b.page)
+            .then(a.y.total_cmp(&b.y))
+            .then(a.x.total_cmp(&b.x))
+    });
+    let mut lines: Vec<(u32, f64, f64, Vec<Glyph>)> = Vec::new();
+    glyphs.into_iter().for_each(|glyph| match lines.last_mut() {
+        Some((page, y, size, line))
+            if *page == glyph.page && (glyph.y - *y).abs() < *size * 0.5 =>
+        {
+            *size = size.max(glyph.size);
+            line.push(glyph);
+        }
+        _ => lines.push((glyph.page, glyph.y, glyph.size, vec![glyph])),
+    });
+
+    lines
+        .into_iter()
+        .map(|(page, y, size, mut glyphs)| {
+            glyphs.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let mut words: Vec<Word> = Vec::new();
+            let mut previous_right = f64::MIN;
+            glyphs.into_iter().for_each(|glyph| {
+                if glyph.text.trim().is_empty() {
+                    previous_right = f64::MIN;
+                    return;
+                }
+                match words.last_mut() {
+                    Some(word) if glyph.x - previous_right < glyph.size * 0.2 => {
+                        word.right = glyph.x + glyph.width;
+                        word.text.push_str(&glyph.text);
+                    }
+                    _ => words.push(Word {
+                        left: glyph.x,
+                        right: glyph.x + glyph.width,
+                        text: glyph.text.clone(),
+                    }),
+                }
+                previous_right = glyph.x + glyph.width;
+            });
+            Line {
+                page,
+                y,
+                size,
+                words,
+            }
+        })
+        .filter(|line| !line.words.is_empty())
+        .collect()
+}
+
+// header names are compared without case and diacritics
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'ă' | 'â' => 'a',
+            'î' => 'i',
+            'ș' | 'ş' => 's',
+            'ț' | 'ţ' => 't',
+            _ => c,
+        })
+        .collect()
+}
+
+// words separated by less than their height belong to the same header cell, even when the
+// header is written on two lines
+fn header_cells(words: Vec<Word>, size: f64) -> Vec<Word> {
+    let mut words = words;
+    words.sort_by(|a, b| a.left.total_cmp(&b.left));
+    let mut cells: Vec<Word> = Vec::new();
+    words.into_iter().for_each(|word| match cells.last_mut() {
+        Some(cell) if word.left - cell.right < size => {
+            cell.right = cell.right.max(word.right);
+            cell.text = format!("{} {}", cell.text, word.text);
+        }
+        _ => cells.push(word),
+    });
+    cells
+}
+
+// a header matches when it is the name or begins with its words, so "Data" finds "Data scadentei"
+// but not "Sold la data"; a name that only begins several headers matches none of them
+fn find_column(headers: &[String], name: &str) -> Option<usize> {
+    let name = normalize(name);
+    let headers = headers
+        .iter()
+        .map(|header| normalize(header))
+        .collect::<Vec<_>>();
+    if let Some(position) = headers.iter().position(|header| *header == name) {
+        return Some(position);
+    }
+    let mut positions = headers.iter().enumerate().filter_map(|(position, header)| {
+        header
+            .strip_prefix(&name)
+            .is_some_and(|rest| rest.starts_with(' '))
+            .then_some(position)
+    });
+    match (positions.next(), positions.next()) {
+        (Some(position), None) => Some(position),
+        _ => None,
+    }
+}
+
+// the index of the column a word belongs to: the one it overlaps most, otherwise the closest one
+fn column_of(word: &Word, columns: &[Word]) -> usize {
+    let overlap = |column: &Word| word.right.min(column.right) - word.left.max(column.left);
+    let distance =
+        |column: &Word| ((word.left + word.right) / 2.0 - (column.left + column.right) / 2.0).abs();
+    (0..columns.len())
+        .max_by(|&a, &b| {
+            let (a, b) = (&columns[a], &columns[b]);
+            match (overlap(a) > 0.0, overlap(b) > 0.0) {
+                (true, true) => overlap(a).total_cmp(&overlap(b)),
+                (true, false) => std::cmp::Ordering::Greater,
+                (false, true) => std::cmp::Ordering::Less,
+                (false, false) => distance(b).total_cmp(&distance(a)),
+            }
+        })
+        .unwrap_or(0)
+}
+
+pub(crate) struct Table {
+    pub(crate) headers: Vec<String>,
+    pub(crate) rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub(crate) fn cell<'a>(&self, row: &'a [String], name: &str) -> Option<&'a str> {
+        let column = find_column(&self.headers, name)?;
+        row.get(column)
+            .map(String::as_str)
+            .filter(|cell| !cell.is_empty())
+    }
+}
+
+// rebuilds the table whose header names every column in `header_names`, across all pages;
+// the header is looked for again on every page and the rows above it are ignored
+pub(crate) fn extract_table(document: &Document, header_names: &[&str]) -> Result<Table, String> {
+    let mut collector = GlyphCollector::default();
+    pdf_extract::output_doc(document, &mut collector)
+        .map_err(|error| format!("Fișierul PDF nu a putut fi citit : {}", error))?;
+    let lines = lines(collector.glyphs);
+
+    let is_header = |cells: &[Word]| {
+        let headers = cells
+            .iter()
+            .map(|cell| cell.text.clone())
+            .collect::<Vec<_>>();
+        header_names
+            .iter()
+            .all(|name| find_column(&headers, name).is_some())
+    };
+    // the header cells starting at this line, which may continue on the next one
+    let header_at = |index: usize| {
+        let line = &lines[index];
+        let cells = header_cells(line.words.clone(), line.size);
+        if is_header(&cells) {
+            return Some((cells, 1));
+        }
+        let next = lines.get(index + 1)?;
+        if next.page != line.page || next.y - line.y > line.size * 1.6 {
+            return None;
+        }
+        let words = line.words.iter().chain(&next.words).cloned().collect();
+        let cells = header_cells(words, line.size);
+        is_header(&cells).then_some((cells, 2))
+    };
+
+    let mut columns: Option<Vec<Word>> = None;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    // the page and position of the last line added to a row, to join wrapped cells
+    let mut last_line: Option<(u32, f64)> = None;
+    let mut page = 0;
+    let mut index = 0;
+    while index < lines.len() {
+        let line = &lines[index];
+        if line.page != page {
+            page = line.page;
+            last_line = None;
+            // the lines above the header of a page are page headers
+            if let Some(header) = (index..lines.len())
+                .take_while(|&candidate| lines[candidate].page == page)
+                .find_map(|candidate| header_at(candidate).map(|header| (candidate, header)))
+            {
+                let (candidate, (cells, length)) = header;
+                columns = Some(cells);
+                index = candidate + length;
+                continue;
+            }
+        }
+        let Some(columns) = &columns else {
+            index += 1;
+            continue;
+        };
+
+        let mut cells = vec![String::new(); columns.len()];
+        line.words.iter().for_each(|word| {
+            let cell = &mut cells[column_of(word, columns)];
+            if !cell.is_empty() {
+                cell.push(' ');
+            }
+            cell.push_str(&word.text);
+        });
+        // a line without anything in the first column, right under a row, continues its cells
+        let continues = cells[0].is_empty()
+            && last_line
+                .is_some_and(|(page, y)| page == line.page && line.y - y <= line.size * 1.6);
+        match (continues, rows.last_mut()) {
+            (true, Some(row)) => row.iter_mut().zip(cells).for_each(|(cell, more)| {
+                if !more.is_empty() {
+                    if !cell.is_empty() {
+                        cell.push(' ');
+                    }
+                    cell.push_str(&more);
+                }
+            }),
+            _ => rows.push(cells),
+        }
+        last_line = Some((line.page, line.y));
+        index += 1;
+    }
+
+    match columns {
+        Some(columns) => Ok(Table {
+            headers: columns.into_iter().map(|column| column.text).collect(),
+            rows,
+        }),
+        None => Err(format!(
+            "Tabelul cu coloanele {} nu a fost găsit în fișierul PDF.",
+            header_names.join(", ")
+        )),
+    }
+}
+
+// the names of the columns a bank prints in its schedule table
+pub(crate) struct TableLayout {
+    pub(crate) date: &'static str,
+    pub(crate) principal: &'static str,
+    pub(crate) interest: &'static str,
+    pub(crate) insurance: Option<&'static str>,
+    pub(crate) commission: Option<&'static str>,
+    pub(crate) balance: Option<&'static str>,
+}
+
+// the installment of a row, none for the rows without a valid date (notes, subtotals, totals or
+// page footers) or without a principal; an amount that is not a number is an error, not a zero
+fn row_installment(
+    table: &Table,
+    row: &[String],
+    layout: &TableLayout,
+    locale: Locale,
+) -> Option<Result<Installment, String>> {
+    let date = locale.parse_date(table.cell(row, layout.date)?)?;
+    let amount = |name: Option<&str>, label: &str| match name.and_then(|name| table.cell(row, name))
+    {
+        Some(cell) => text_amount(cell, locale)
+            .map_err(|text| format!("Rata din {}: {} \"{}\" nu este o sumă.", date, label, text)),
+        None => Ok(None),
+    };
+    let principal = match amount(Some(layout.principal), "principalul") {
+        Ok(principal) => principal?,
+        Err(error) => return Some(Err(error)),
+    };
+    let read = || {
+        Ok(Installment {
+            insurance: amount(layout.insurance, "asigurarea")?.unwrap_or(0.0),
+            commission: amount(layout.commission, "comisionul")?.unwrap_or(0.0),
+            balance: amount(layout.balance, "soldul")?,
+            ..Installment::new(
+                date,
+                principal,
+                amount(Some(layout.interest), "dobânda")?.unwrap_or(0.0),
+            )
+        })
+    };
+    Some(read())
+}
+
+// none when the document has no table with the columns of the layout
+pub(crate) fn extract_schedule(
+    document: &Document,
+    layout: &TableLayout,
+) -> Result<Option<Schedule>, String> {
+    let Ok(table) = extract_table(document, &[layout.date, layout.principal, layout.interest])
+    else {
+        return Ok(None);
+    };
+    let locale = Locale::detect(&table.rows.concat().join(" "));
+    let installments = table
+        .rows
+        .iter()
+        .filter_map(|row| row_installment(&table, row, layout, locale))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let totals = table
+        .rows
+        .iter()
+        .rev()
+        .find(|row| {
+            row.iter()
+                .find(|cell| !cell.is_empty())
+                .is_some_and(|cell| cell.to_lowercase().starts_with("total"))
+        })
+        .map(|row| {
+            let amount = |name: Option<&str>| {
+                name.and_then(|name| table.cell(row, name))
+                    .and_then(|cell| locale.parse_amount(cell))
+            };
+            PrintedTotals {
+                principal: amount(Some(layout.principal)),
+                interest: amount(Some(layout.interest)),
+                insurance: amount(layout.insurance),
+                commission: amount(layout.commission),
+            }
+        });
+    Ok(Some(Schedule {
+        totals,
+        ..Schedule::new(installments)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the glyphs of `text` written from `x` on the baseline `y`, 10 points high and 5 wide
+    fn glyphs(page: u32, x: f64, y: f64, text: &str) -> Vec<Glyph> {
+        text.chars()
+            .enumerate()
+            .map(|(index, c)| Glyph {
+                page,
+                x: x + index as f64 * 5.0,
+                y,
+                width: 5.0,
+                size: 10.0,
+                text: c.to_string(),
+            })
+            .collect()
+    }
+
+    fn word(left: f64, right: f64) -> Word {
+        Word {
+            left,
+            right,
+            text: String::new(),
+        }
+    }
+
+    fn texts(line: &Line) -> Vec<&str> {
+        line.words.iter().map(|word| word.text.as_str()).collect()
+    }
+
+    #[test]
+    fn groups_glyphs_into_lines_and_words() {
+        let mut all = [
+            glyphs(1, 100.0, 200.0, "Principal"),
+            // a baseline off by less than half the height is the same line
+            glyphs(1, 20.0, 202.0, "Data"),
+            glyphs(1, 20.0, 215.0, "15.01.2025"),
+            glyphs(1, 100.0, 215.0, "1.000,00"),
+            glyphs(2, 20.0, 200.0, "Total"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        all.reverse();
+        let lines = lines(all);
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(texts(&lines[0]), ["Data", "Principal"]);
+        assert_eq!(texts(&lines[1]), ["15.01.2025", "1.000,00"]);
+        assert_eq!((lines[2].page, texts(&lines[2])), (2, vec!["Total"]));
+        assert_eq!(lines[1].words[1].left, 100.0);
+        assert_eq!(lines[1].words[1].right, 140.0);
+    }
+
+    #[test]
+    fn splits_words_on_spaces_and_gaps() {
+        let spaced = [
+            glyphs(1, 20.0, 100.0, "1 234,56"),
+            glyphs(1, 70.0, 100.0, "Sold"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        assert_eq!(texts(&lines(spaced)[0]), ["1", "234,56", "Sold"]);
+    }
+
+    #[test]
+    fn merges_header_words_closer_than_their_height() {
+        let line = lines(
+            [
+                glyphs(1, 20.0, 100.0, "Data"),
+                glyphs(1, 45.0, 100.0, "scadentei"),
+                glyphs(1, 150.0, 100.0, "Principal"),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        )
+        .remove(0);
+        let cells = header_cells(line.words, line.size);
+        let headers = cells
+            .iter()
+            .map(|cell| cell.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(headers, ["Data scadentei", "Principal"]);
+        assert_eq!((cells[0].left, cells[0].right), (20.0, 90.0));
+    }
+
+    #[test]
+    fn assigns_words_to_the_overlapping_or_the_closest_column() {
+        let columns = [word(20.0, 60.0), word(100.0, 140.0), word(200.0, 260.0)];
+        // the column it overlaps most, even when it touches a neighbour
+        assert_eq!(column_of(&word(45.0, 105.0), &columns), 0);
+        assert_eq!(column_of(&word(90.0, 150.0), &columns), 1);
+        // amounts aligned to the right of a narrow header overlap nothing
+        assert_eq!(column_of(&word(145.0, 170.0), &columns), 1);
+        assert_eq!(column_of(&word(175.0, 195.0), &columns), 2);
+        assert_eq!(column_of(&word(300.0, 320.0), &columns), 2);
+    }
+
+    #[test]
+    fn finds_columns_by_name_or_by_a_unique_prefix() {
+        let headers = [
+            "Nr.",
+            "Data scadenței",
+            "Sold la data",
+            "Principal",
+            "Dobândă",
+        ]
+        .map(String::from);
+        assert_eq!(find_column(&headers, "Dobanda"), Some(4));
+        assert_eq!(find_column(&headers, "Data"), Some(1));
+        assert_eq!(find_column(&headers, "Sold"), Some(2));
+        assert_eq!(find_column(&headers, "Scadentei"), None);
+
+        let headers = ["Sold initial", "Sold final", "Data"].map(String::from);
+        assert_eq!(find_column(&headers, "Data"), Some(2));
+        assert_eq!(find_column(&headers, "Sold"), None);
+        assert_eq!(find_column(&headers, "Sold final"), Some(1));
+    }
+
+    #[test]
+    fn reads_the_rows_of_the_table_and_rejects_amounts_that_are_not_numbers() {
+        let layout = TableLayout {
+            date: "Data",
+            principal: "Principal",
+            interest: "Dobanda",
+            insurance: None,
+            commission: Some("Comision"),
+            balance: None,
+        };
+        let table = Table {
+            headers: ["Data", "Principal", "Dobanda", "Comision"]
+                .map(String::from)
+                .to_vec(),
+            rows: vec![
+                ["15.01.2024", "100,00", "50,00", "-"]
+                    .map(String::from)
+                    .to_vec(),
+                ["Total", "100,00", "50,00", ""].map(String::from).to_vec(),
+                ["15.02.2024", "", "", ""].map(String::from).to_vec(),
+                ["15.03.2024", "101,00", "inclusă", ""]
+                    .map(String::from)
+                    .to_vec(),
+            ],
+        };
+        let locale = Locale::detect("15.01.2024 100,00 50,00");
+        let installments = table.rows[..3]
+            .iter()
+            .filter_map(|row| row_installment(&table, row, &layout, locale))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(installments.len(), 1);
+        assert_eq!(installments[0].interest, 50.0);
+        assert_eq!(installments[0].commission, 0.0);
+        assert_eq!(
+            row_installment(&table, &table.rows[3], &layout, locale)
+                .unwrap()
+                .err()
+                .as_deref(),
+            Some("Rata din 2024-03-15: dobânda \"inclusă\" nu este o sumă.")
+        );
+    }
+}
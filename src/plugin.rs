@@ -0,0 +1,196 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::prepayment::PrepaymentRules;
+use crate::schedule::Schedule;
+use crate::schedulejson;
+
+// what the plugin receives on stdin
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum PluginInput {
+    // the path of the PDF file, on a single line
+    #[default]
+    Path,
+    // the text extracted from the PDF file
+    Text,
+}
+
+// an external program that reads a schedule and prints it in the JSON schedule format
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ParserPlugin {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) keywords: Vec<String>,
+    pub(crate) command: String,
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+    #[serde(default)]
+    pub(crate) input: PluginInput,
+    #[serde(default = "default_timeout_seconds")]
+    pub(crate) timeout_seconds: u64,
+    #[serde(default)]
+    pub(crate) only_on_due_dates: bool,
+    #[serde(default)]
+    pub(crate) notice_days: u64,
+    // the folder of the plugin file, relative commands are looked up there first
+    #[serde(skip)]
+    directory: PathBuf,
+}
+
+fn default_timeout_seconds() -> u64 {
+    30
+}
+
+impl ParserPlugin {
+    pub(crate) fn prepayment_rules(&self) -> PrepaymentRules {
+        PrepaymentRules {
+            only_on_due_dates: self.only_on_due_dates,
+            notice_days: self.notice_days,
+        }
+    }
+
+    fn program(&self) -> PathBuf {
+        let program = self.directory.join(&self.command);
+        match program.is_file() {
+            true => program,
+            false => PathBuf::from(&self.command),
+        }
+    }
+
+    // runs the plugin and waits at most `timeout_seconds` for it to finish
+    fn run(&self, input: Vec<u8>) -> Result<Vec<u8>, String> {
+        let mut child = Command::new(self.program())
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| {
+                format!(
+                    "Pluginul {} nu a putut fi pornit ({}) : {}",
+                    self.name, self.command, error
+                )
+            })?;
+
+        // the pipes are served on separate threads so that a plugin writing a lot cannot block
+        let stdin = child.stdin.take();
+        std::thread::spawn(move || {
+            if let Some(mut stdin) = stdin {
+                let _ = stdin.write_all(&input);
+            }
+        });
+        let read_all = |pipe: Option<Box<dyn Read + Send>>| {
+            std::thread::spawn(move || {
+                let mut output = Vec::new();
+                if let Some(mut pipe) = pipe {
+                    let _ = pipe.read_to_end(&mut output);
+                }
+                output
+            })
+        };
+        let stdout = read_all(child.stdout.take().map(|pipe| Box::new(pipe) as _));
+        let stderr = read_all(child.stderr.take().map(|pipe| Box::new(pipe) as _));
+
+        let deadline = Instant::now() + Duration::from_secs(self.timeout_seconds);
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!(
+                        "Pluginul {} nu a terminat în {} secunde și a fost oprit.",
+                        self.name, self.timeout_seconds
+                    ));
+                }
+                Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+                Err(error) => {
+                    return Err(format!("Pluginul {} a eșuat : {}", self.name, error));
+                }
+            }
+        };
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(&stderr);
+            return Err(format!(
+                "Pluginul {} a eșuat ({}) : {}",
+                self.name,
+                status,
+                stderr.trim()
+            ));
+        }
+        Ok(stdout)
+    }
+
+    pub(crate) fn extract_payment_data(
+        &self,
+        pdf_file: &Path,
+        text: &str,
+    ) -> Result<Schedule, String> {
+        let input = match self.input {
+            PluginInput::Path => format!("{}\n", pdf_file.display()).into_bytes(),
+            PluginInput::Text => text.as_bytes().to_vec(),
+        };
+        let output = self.run(input)?;
+        let json = String::from_utf8(output)
+            .map_err(|_| format!("Pluginul {} nu a răspuns cu text UTF-8.", self.name))?;
+        let document = schedulejson::parse_document(&json)
+            .map_err(|error| format!("Pluginul {} : {}", self.name, error))?;
+        Ok(schedulejson::to_schedule(&document))
+    }
+}
+
+// plugins are described by TOML files in the "plugins" folder next to the executable
+fn plugin_directory() -> Option<PathBuf> {
+    std::env::current_exe().ok().and_then(|executable| {
+        executable
+            .parent()
+            .map(|directory| directory.join("plugins"))
+    })
+}
+
+pub(crate) fn load_plugins() -> Vec<ParserPlugin> {
+    let Some(directory) = plugin_directory() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&directory) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        })
+        .filter_map(|path| {
+            let plugin = std::fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| {
+                    toml::from_str::<ParserPlugin>(&text).map_err(|error| error.to_string())
+                });
+            let plugin = plugin.and_then(|plugin| match plugin.name.trim().is_empty() {
+                true => Err("numele pluginului lipsește".to_string()),
+                false => Ok(plugin),
+            });
+            match plugin {
+                Ok(plugin) => Some(ParserPlugin {
+                    directory: directory.clone(),
+                    ..plugin
+                }),
+                Err(error) => {
+                    eprintln!("Pluginul {} a fost ignorat : {}", path.display(), error);
+                    None
+                }
+            }
+        })
+        .collect()
+}
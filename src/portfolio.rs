@@ -0,0 +1,350 @@
+use chrono::Datelike;
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::daycount::DayCount;
+use crate::prepayment::{accrual_periods, Period};
+use crate::schedule::{Installment, Schedule};
+use crate::table::print_table;
+
+#[derive(EnumIter, Debug, Clone, Copy)]
+enum Strategy {
+    HighestRate,
+    SmallestBalance,
+    BestReturn,
+}
+
+impl Strategy {
+    fn name(&self) -> &'static str {
+        match self {
+            Strategy::HighestRate => "Dobânda cea mai mare",
+            Strategy::SmallestBalance => "Soldul cel mai mic",
+            Strategy::BestReturn => "Randamentul cel mai bun",
+        }
+    }
+}
+
+struct Loan<'a> {
+    name: &'a str,
+    installments: &'a [Installment],
+    periods: Vec<Period>,
+    // the yearly rate printed in the header of the schedule
+    annual_rate: Option<f64>,
+}
+
+impl Loan<'_> {
+    fn installment_in(&self, month: (i32, u32)) -> Option<usize> {
+        self.installments
+            .iter()
+            .position(|installment| (installment.date.year(), installment.date.month()) == month)
+    }
+
+    // the rate from the header, otherwise the one implied by the installment
+    fn annual_rate(&self, index: usize) -> f64 {
+        self.annual_rate
+            .map(|rate| rate / 100.0)
+            .unwrap_or(self.periods[index].annual_rate)
+    }
+
+    // annualized cost of every unit still owed, insurance included; the fixed commissions are paid
+    // until the loan is closed whatever is prepaid, so they are not saved by prepaying
+    fn cost_rate(&self, index: usize) -> f64 {
+        let period = &self.periods[index];
+        let installment = &self.installments[index];
+        DayCount::Actual365.implied_annual_rate(
+            period.balance,
+            installment.interest + installment.insurance,
+            period.start,
+            period.end,
+        )
+    }
+}
+
+struct MonthResult {
+    month: (i32, u32),
+    balances: Vec<f64>,
+    extra_payment: f64,
+    total_payment: f64,
+}
+
+struct Simulation {
+    months: Vec<MonthResult>,
+    total_cost: f64,
+}
+
+fn simulate(loans: &[Loan], budget: f64, strategy: Option<Strategy>) -> Simulation {
+    let months = loans
+        .iter()
+        .flat_map(|loan| loan.installments.iter())
+        .map(|installment| (installment.date.year(), installment.date.month()))
+        .collect::<std::collections::BTreeSet<_>>();
+
+    let mut prepaid = vec![0.0; loans.len()];
+    let mut total_cost = 0.0;
+    let months = months
+        .into_iter()
+        .map(|month| {
+            let mut available = match strategy {
+                Some(_) => budget,
+                None => 0.0,
+            };
+            let mut total_payment = 0.0;
+            let mut extra_payment = 0.0;
+            // the next installment and the balance after this month's payment
+            let mut open_loans = Vec::new();
+            let mut balances = vec![0.0; loans.len()];
+
+            loans.iter().enumerate().for_each(|(l, loan)| {
+                let Some(k) = loan.installment_in(month) else {
+                    return;
+                };
+                let installment = &loan.installments[k];
+                let scheduled_balance = loan.periods[k].balance;
+                let balance = (scheduled_balance - prepaid[l]).max(0.0);
+                if balance <= 0.0 {
+                    // a closed loan frees its installment for the others
+                    if strategy.is_some() {
+                        available += installment.total();
+                    }
+                    return;
+                }
+
+                let ratio = balance / scheduled_balance;
+                let cost =
+                    (installment.interest + installment.insurance) * ratio + installment.commission;
+                let principal = installment.principal.min(balance);
+                total_payment += principal + cost;
+                total_cost += cost;
+
+                balances[l] = balance - principal;
+                if balances[l] > 0.0 {
+                    open_loans.push((l, k));
+                }
+            });
+
+            if let Some(strategy) = strategy {
+                match strategy {
+                    Strategy::HighestRate => open_loans.sort_by(|(a, i), (b, j)| {
+                        loans[*b]
+                            .annual_rate(*j)
+                            .total_cmp(&loans[*a].annual_rate(*i))
+                    }),
+                    Strategy::SmallestBalance => {
+                        open_loans.sort_by(|(a, _), (b, _)| balances[*a].total_cmp(&balances[*b]))
+                    }
+                    Strategy::BestReturn => open_loans.sort_by(|(a, i), (b, j)| {
+                        loans[*b].cost_rate(*j).total_cmp(&loans[*a].cost_rate(*i))
+                    }),
+                }
+                open_loans.iter().for_each(|(l, _)| {
+                    let amount = available.min(balances[*l]);
+                    prepaid[*l] += amount;
+                    balances[*l] -= amount;
+                    available -= amount;
+                    extra_payment += amount;
+                });
+            }
+
+            MonthResult {
+                month,
+                balances,
+                extra_payment,
+                total_payment: total_payment + extra_payment,
+            }
+        })
+        .collect();
+
+    Simulation { months, total_cost }
+}
+
+fn last_payment_month(simulation: &Simulation) -> String {
+    simulation
+        .months
+        .iter()
+        .rev()
+        .find(|month| month.total_payment > 0.0)
+        .map(|month| format!("{}-{:02}", month.month.0, month.month.1))
+        .unwrap_or_default()
+}
+
+// the budget is an amount in a single currency, shared only between loans in that currency
+fn portfolio_currency(schedules: &[(String, Schedule)]) -> Result<&str, String> {
+    let currencies = schedules
+        .iter()
+        .map(|(_, schedule)| schedule.currency())
+        .collect::<std::collections::BTreeSet<_>>();
+    match currencies.len() {
+        0 | 1 => Ok(currencies.first().copied().unwrap_or("RON")),
+        _ => Err(format!(
+            "Creditele sunt în monede diferite ({}), bugetul lunar poate fi împărțit doar între credite în aceeași monedă.",
+            currencies.into_iter().collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+pub(crate) fn print_portfolio_results(
+    schedules: &[(String, Schedule)],
+    budget: f64,
+) -> Result<(), String> {
+    let currency = portfolio_currency(schedules)?;
+    let today = chrono::Local::now().date_naive();
+    let remaining = schedules
+        .iter()
+        .map(|(name, schedule)| {
+            (
+                name,
+                schedule.loan.annual_rate,
+                Schedule::new(
+                    schedule
+                        .installments
+                        .iter()
+                        .filter(|installment| installment.date > today)
+                        .cloned()
+                        .collect(),
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+    let loans = remaining
+        .iter()
+        .map(|(name, annual_rate, schedule)| Loan {
+            name,
+            installments: &schedule.installments,
+            periods: accrual_periods(schedule, DayCount::Actual365),
+            annual_rate: *annual_rate,
+        })
+        .collect::<Vec<_>>();
+
+    let baseline = simulate(&loans, 0.0, None);
+    let headers = ["Lună".to_string()]
+        .into_iter()
+        .chain(loans.iter().map(|loan| format!("Sold {}", loan.name)))
+        .chain(["Plată suplimentară".to_string(), "Total plătit".to_string()])
+        .collect::<Vec<_>>();
+    let headers = headers.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let summary = Strategy::iter()
+        .map(|strategy| {
+            let simulation = simulate(&loans, budget, Some(strategy));
+
+            println!();
+            println!(
+                "Strategia \"{}\", {:.2} {} pe lună",
+                strategy.name(),
+                budget,
+                currency
+            );
+            let rows = simulation
+                .months
+                .iter()
+                .take_while(|month| month.total_payment > 0.0)
+                .map(|month| {
+                    [format!("{}-{:02}", month.month.0, month.month.1)]
+                        .into_iter()
+                        .chain(
+                            month
+                                .balances
+                                .iter()
+                                .map(|balance| format!("{:.2} {}", balance, currency)),
+                        )
+                        .chain([
+                            format!("{:.2} {}", month.extra_payment, currency),
+                            format!("{:.2} {}", month.total_payment, currency),
+                        ])
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            print_table(&headers, &rows);
+
+            vec![
+                strategy.name().to_string(),
+                format!("{:.2} {}", simulation.total_cost, currency),
+                format!(
+                    "{:.2} {}",
+                    baseline.total_cost - simulation.total_cost,
+                    currency
+                ),
+                last_payment_month(&simulation),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    println!();
+    print_table(
+        &[
+            "Strategie",
+            "Dobândă și costuri",
+            "Economie",
+            "Ultima plată",
+        ],
+        &[vec![
+            "Fără rambursări anticipate".to_string(),
+            format!("{:.2} {}", baseline.total_cost, currency),
+            format!("{:.2} {}", 0.0, currency),
+            last_payment_month(&baseline),
+        ]]
+        .into_iter()
+        .chain(summary)
+        .collect::<Vec<_>>(),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::schedule::annuity_schedule;
+
+    fn schedule(currency: Option<&str>) -> Schedule {
+        let mut schedule = annuity_schedule(
+            10000.0,
+            0.06,
+            12,
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        );
+        schedule.loan.currency = currency.map(str::to_string);
+        schedule
+    }
+
+    #[test]
+    fn shares_the_budget_only_between_loans_in_the_same_currency() {
+        let loans = [
+            ("a".to_string(), schedule(None)),
+            ("b".to_string(), schedule(Some("RON"))),
+        ];
+        assert_eq!(portfolio_currency(&loans), Ok("RON"));
+        let loans = [
+            ("a".to_string(), schedule(Some("EUR"))),
+            ("b".to_string(), schedule(Some("RON"))),
+            ("c".to_string(), schedule(Some("EUR"))),
+        ];
+        assert_eq!(
+            portfolio_currency(&loans),
+            Err("Creditele sunt în monede diferite (EUR, RON), bugetul lunar poate fi împărțit doar între credite în aceeași monedă.".to_string())
+        );
+    }
+
+    #[test]
+    fn ranks_the_return_without_the_fixed_commissions() {
+        let schedule = schedule(None);
+        let with_commission = schedule
+            .installments
+            .iter()
+            .map(|installment| Installment {
+                commission: 50.0,
+                ..installment.clone()
+            })
+            .collect::<Vec<_>>();
+        let loan = |installments| Loan {
+            name: "credit",
+            installments,
+            periods: accrual_periods(&schedule, DayCount::Actual365),
+            annual_rate: None,
+        };
+        assert_eq!(
+            loan(&with_commission).cost_rate(3),
+            loan(&schedule.installments).cost_rate(3)
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use chrono::{Days, Months, NaiveDate};
+
+use crate::daycount::DayCount;
+use crate::schedule::Schedule;
+use crate::table::print_table;
+
+pub(crate) struct Period {
+    pub(crate) start: NaiveDate,
+    pub(crate) end: NaiveDate,
+    pub(crate) balance: f64,
+    pub(crate) annual_rate: f64,
+}
+
+// the accrual periods of the schedule, each ending on an installment's due date; the first one
+// starts on the date the loan was granted when the header gives it and the schedule lists the
+// whole term, otherwise a month earlier
+pub(crate) fn accrual_periods(schedule: &Schedule, day_count: DayCount) -> Vec<Period> {
+    let balances = schedule.balances_before();
+    let whole_term = schedule
+        .loan
+        .term
+        .is_none_or(|term| schedule.installments.len() >= term as usize);
+    schedule
+        .installments
+        .iter()
+        .enumerate()
+        .map(|(i, installment)| {
+            let start = match i {
+                0 => schedule
+                    .loan
+                    .start_date
+                    .filter(|start_date| whole_term && *start_date < installment.date)
+                    .or_else(|| installment.date.checked_sub_months(Months::new(1)))
+                    .unwrap_or(installment.date),
+                _ => schedule.installments[i - 1].date,
+            };
+            Period {
+                start,
+                end: installment.date,
+                balance: balances[i],
+                annual_rate: day_count.implied_annual_rate(
+                    balances[i],
+                    installment.interest,
+                    start,
+                    installment.date,
+                ),
+            }
+        })
+        .collect()
+}
+
+// interest saved by prepaying `amount` on `date` and keeping the same principal
+// payments, which shortens the term
+pub(crate) fn interest_saved(
+    periods: &[Period],
+    amount: f64,
+    date: NaiveDate,
+    day_count: DayCount,
+) -> f64 {
+    periods
+        .iter()
+        .filter(|period| period.end > date)
+        .map(|period| {
+            let start = period.start.max(date);
+            let reduction = amount.min(period.balance).max(0.0);
+            day_count.accrued_interest(reduction, period.annual_rate, start, period.end)
+        })
+        .sum()
+}
+
+// interest of the first installment due after `date` when `amount` is prepaid on `date`
+pub(crate) fn next_installment_interest(
+    periods: &[Period],
+    amount: f64,
+    date: NaiveDate,
+    day_count: DayCount,
+) -> Option<f64> {
+    let period = periods.iter().find(|period| period.end > date)?;
+    let prepayment_date = period.start.max(date);
+    let new_balance = (period.balance - amount).max(0.0);
+    Some(
+        day_count.accrued_interest(
+            period.balance,
+            period.annual_rate,
+            period.start,
+            prepayment_date,
+        ) + day_count.accrued_interest(
+            new_balance,
+            period.annual_rate,
+            prepayment_date,
+            period.end,
+        ),
+    )
+}
+
+pub(crate) fn print_prepayment_results(schedule: &Schedule, amount: f64, day_count: DayCount) {
+    let currency = schedule.currency();
+    let periods = accrual_periods(schedule, day_count);
+
+    let rows = periods
+        .iter()
+        .enumerate()
+        .filter(|(_, period)| (period.end - period.start).num_days() >= 2)
+        .map(|(index, period)| {
+            let early_date = period.start + Days::new(1);
+            let late_date = period.end - Days::new(1);
+            let early_savings = interest_saved(&periods, amount, early_date, day_count);
+            let late_savings = interest_saved(&periods, amount, late_date, day_count);
+            let early_interest =
+                next_installment_interest(&periods, amount, early_date, day_count).unwrap_or(0.0);
+            let late_interest =
+                next_installment_interest(&periods, amount, late_date, day_count).unwrap_or(0.0);
+            vec![
+                (index + 1).to_string(),
+                period.end.to_string(),
+                format!("{:.2}%", period.annual_rate * 100.0),
+                early_date.to_string(),
+                format!("{:.2} {}", early_interest, currency),
+                format!("{:.2} {}", early_savings, currency),
+                late_date.to_string(),
+                format!("{:.2} {}", late_interest, currency),
+                format!("{:.2} {}", late_savings, currency),
+                format!("{:.2} {}", early_savings - late_savings, currency),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    println!();
+    println!(
+        "Rambursare anticipată de {:.2} {}, dobândă calculată zilnic ({})",
+        amount,
+        currency,
+        day_count.name()
+    );
+    print_table(
+        &[
+            "Număr",
+            "Scadență",
+            "Dobândă anuală",
+            "După scadență",
+            "Dobândă rată",
+            "Economie",
+            "Înainte de scadență",
+            "Dobândă rată",
+            "Economie",
+            "Diferență",
+        ],
+        &rows,
+    );
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PrepaymentRules {
+    pub(crate) only_on_due_dates: bool,
+    pub(crate) notice_days: u64,
+}
+
+// the date on which the bank applies a prepayment requested on `request_date`
+pub(crate) fn effective_date(
+    schedule: &Schedule,
+    request_date: NaiveDate,
+    rules: PrepaymentRules,
+) -> Option<NaiveDate> {
+    let earliest_date = request_date + Days::new(rules.notice_days);
+    match rules.only_on_due_dates {
+        true => schedule
+            .installments
+            .iter()
+            .map(|installment| installment.date)
+            .find(|date| *date >= earliest_date),
+        false => Some(earliest_date),
+    }
+}
+
+// the dates the bank can apply a prepayment requested within the window, each with the last day
+// the request can be made, the interest saved and the deposit interest earned until then
+fn prepayment_candidates(
+    schedule: &Schedule,
+    amount: f64,
+    window: (NaiveDate, NaiveDate),
+    rules: PrepaymentRules,
+    deposit_rate: f64,
+    day_count: DayCount,
+) -> Vec<(NaiveDate, NaiveDate, f64, f64)> {
+    let periods = accrual_periods(schedule, day_count);
+    let (window_start, window_end) = window;
+
+    // for every date the bank can apply the prepayment keep the last day the request can be made
+    let mut candidates: Vec<(NaiveDate, NaiveDate, f64, f64)> = Vec::new();
+    window_start
+        .iter_days()
+        .take_while(|request_date| *request_date <= window_end)
+        .for_each(|request_date| {
+            let Some(effective_date) = effective_date(schedule, request_date, rules) else {
+                return;
+            };
+            let savings = interest_saved(&periods, amount, effective_date, day_count);
+            // the money keeps earning the deposit rate until the bank takes it
+            let deposit_interest = DayCount::Actual365.accrued_interest(
+                amount,
+                deposit_rate / 100.0,
+                window_start,
+                effective_date,
+            );
+            match candidates.last_mut() {
+                Some(candidate) if candidate.1 == effective_date => candidate.0 = request_date,
+                _ => candidates.push((request_date, effective_date, savings, deposit_interest)),
+            }
+        });
+    candidates
+}
+
+pub(crate) fn print_best_prepayment_dates(
+    schedule: &Schedule,
+    amount: f64,
+    window: (NaiveDate, NaiveDate),
+    rules: PrepaymentRules,
+    deposit_rate: f64,
+    day_count: DayCount,
+) {
+    let currency = schedule.currency();
+    let (window_start, window_end) = window;
+    let mut candidates =
+        prepayment_candidates(schedule, amount, window, rules, deposit_rate, day_count);
+
+    println!();
+    println!(
+        "Cea mai bună dată pentru rambursarea anticipată a {:.2} {} între {} și {}",
+        amount, currency, window_start, window_end
+    );
+    if candidates.is_empty() {
+        println!("Nicio dată din interval nu respectă regulile băncii.");
+        return;
+    }
+
+    let best_total = candidates
+        .iter()
+        .map(|(_, _, savings, deposit_interest)| savings + deposit_interest)
+        .fold(f64::MIN, f64::max);
+
+    candidates.sort_by(|a, b| (b.2 + b.3).total_cmp(&(a.2 + a.3)));
+    candidates.truncate(10);
+
+    let rows = candidates
+        .iter()
+        .map(
+            |(request_date, effective_date, savings, deposit_interest)| {
+                vec![
+                    request_date.to_string(),
+                    effective_date.to_string(),
+                    format!("{:.2} {}", savings, currency),
+                    format!("{:.2} {}", deposit_interest, currency),
+                    format!("{:.2} {}", savings + deposit_interest, currency),
+                    match savings + deposit_interest == best_total {
+                        true => "*".to_string(),
+                        false => String::new(),
+                    },
+                ]
+            },
+        )
+        .collect::<Vec<_>>();
+
+    print_table(
+        &[
+            "Ultima zi de cerere",
+            "Dată aplicare",
+            "Economie",
+            "Dobândă depozit",
+            "Total",
+            "Optim",
+        ],
+        &rows,
+    );
+    println!(
+        "Reguli: {}, preaviz de {} zile.",
+        match rules.only_on_due_dates {
+            true => "doar la scadență",
+            false => "în orice zi",
+        },
+        rules.notice_days
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::Installment;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn period(start: NaiveDate, end: NaiveDate, balance: f64) -> Period {
+        Period {
+            start,
+            end,
+            balance,
+            annual_rate: 0.06,
+        }
+    }
+
+    // installments due on the 15th of every month from January to April 2024, about 6% a year
+    fn schedule() -> Schedule {
+        Schedule::new(
+            (1..=4)
+                .map(|month| Installment {
+                    balance: Some(100000.0 - 1000.0 * month as f64),
+                    ..Installment::new(date(2024, month, 15), 1000.0, 500.0)
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn saves_the_interest_of_the_prepaid_amount_until_each_due_date() {
+        let periods = [
+            period(date(2024, 1, 15), date(2024, 2, 15), 100000.0),
+            period(date(2024, 2, 15), date(2024, 3, 15), 99000.0),
+        ];
+        // 10 days of the first period and the 29 days of February 2024
+        let saved = interest_saved(&periods, 10000.0, date(2024, 2, 5), DayCount::Actual360);
+        assert!((saved - 10000.0 * 0.06 * 39.0 / 360.0).abs() < 1e-9);
+        // the reduction cannot exceed the balance
+        let saved = interest_saved(&periods, 200000.0, date(2024, 2, 15), DayCount::Actual360);
+        assert!((saved - 99000.0 * 0.06 * 29.0 / 360.0).abs() < 1e-9);
+        assert_eq!(
+            interest_saved(&periods, 10000.0, date(2024, 3, 15), DayCount::Actual360),
+            0.0
+        );
+
+        // the next installment pays the old balance until the prepayment and the new one after it
+        let interest =
+            next_installment_interest(&periods, 10000.0, date(2024, 2, 5), DayCount::Actual360);
+        let expected = (100000.0 * 21.0 + 90000.0 * 10.0) * 0.06 / 360.0;
+        assert!((interest.unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn applies_the_notice_and_the_due_dates_of_the_bank() {
+        let schedule = schedule();
+        let rules = PrepaymentRules {
+            only_on_due_dates: true,
+            notice_days: 10,
+        };
+        assert_eq!(
+            effective_date(&schedule, date(2024, 2, 5), rules),
+            Some(date(2024, 2, 15))
+        );
+        assert_eq!(
+            effective_date(&schedule, date(2024, 2, 6), rules),
+            Some(date(2024, 3, 15))
+        );
+        assert_eq!(effective_date(&schedule, date(2024, 4, 10), rules), None);
+        assert_eq!(
+            effective_date(
+                &schedule,
+                date(2024, 2, 6),
+                PrepaymentRules {
+                    only_on_due_dates: false,
+                    notice_days: 10,
+                }
+            ),
+            Some(date(2024, 2, 16))
+        );
+    }
+
+    #[test]
+    fn keeps_the_last_request_date_of_every_application_date() {
+        let rules = PrepaymentRules {
+            only_on_due_dates: true,
+            notice_days: 0,
+        };
+        let candidates = prepayment_candidates(
+            &schedule(),
+            10000.0,
+            (date(2024, 2, 1), date(2024, 2, 20)),
+            rules,
+            0.0,
+            DayCount::Actual365,
+        );
+        assert_eq!(
+            candidates
+                .iter()
+                .map(|(request_date, effective_date, _, _)| (*request_date, *effective_date))
+                .collect::<Vec<_>>(),
+            [
+                (date(2024, 2, 15), date(2024, 2, 15)),
+                (date(2024, 2, 20), date(2024, 3, 15)),
+            ]
+        );
+        // without deposit interest the earlier prepayment saves more
+        assert!(candidates[0].2 > candidates[1].2);
+        assert_eq!(candidates[0].3, 0.0);
+
+        // the deposit interest earned until the later date can make it the better one
+        let candidates = prepayment_candidates(
+            &schedule(),
+            10000.0,
+            (date(2024, 2, 1), date(2024, 2, 20)),
+            rules,
+            10.0,
+            DayCount::Actual365,
+        );
+        assert!(candidates[1].2 + candidates[1].3 > candidates[0].2 + candidates[0].3);
+    }
+}
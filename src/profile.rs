@@ -0,0 +1,204 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::csvimport::{detect_locale, parse_date};
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::prepayment::PrepaymentRules;
+use crate::schedule::{Installment, Schedule};
+
+// a bank layout described in a TOML file instead of code, columns are numbered from 1
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ParserProfile {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) keywords: Vec<String>,
+    #[serde(default)]
+    pub(crate) skip_lines: usize,
+    #[serde(default)]
+    pub(crate) skip_prefixes: Vec<String>,
+    #[serde(default = "default_merge_lines")]
+    pub(crate) merge_lines: usize,
+    #[serde(default)]
+    pub(crate) min_words: usize,
+    pub(crate) max_words: Option<usize>,
+    pub(crate) date_column: usize,
+    #[serde(default = "default_date_words")]
+    pub(crate) date_words: usize,
+    pub(crate) date_format: Option<String>,
+    pub(crate) decimal_separator: Option<char>,
+    pub(crate) principal_column: usize,
+    pub(crate) interest_column: usize,
+    pub(crate) insurance_column: Option<usize>,
+    pub(crate) commission_column: Option<usize>,
+    pub(crate) balance_column: Option<usize>,
+    #[serde(default)]
+    pub(crate) only_on_due_dates: bool,
+    #[serde(default)]
+    pub(crate) notice_days: u64,
+}
+
+fn default_merge_lines() -> usize {
+    1
+}
+
+fn default_date_words() -> usize {
+    1
+}
+
+impl ParserProfile {
+    pub(crate) fn prepayment_rules(&self) -> PrepaymentRules {
+        PrepaymentRules {
+            only_on_due_dates: self.only_on_due_dates,
+            notice_days: self.notice_days,
+        }
+    }
+
+    fn date(&self, words: &[&str], locale: Locale) -> Option<NaiveDate> {
+        let start = self.date_column.checked_sub(1)?;
+        let date = words.get(start..start + self.date_words)?.join(" ");
+        parse_date(&date, self.date_format.as_deref(), locale)
+    }
+
+    fn amount(&self, words: &[&str], column: usize, locale: Locale) -> Option<f64> {
+        locale.parse_amount(words.get(column.checked_sub(1)?)?)
+    }
+
+    pub(crate) fn extract_payment_data(&self, text: &str) -> Schedule {
+        let locale = detect_locale(text.split_whitespace(), self.decimal_separator);
+        let lines = text
+            .lines()
+            .skip(self.skip_lines)
+            .filter(|line| {
+                !self
+                    .skip_prefixes
+                    .iter()
+                    .any(|prefix| line.trim_start().starts_with(prefix.as_str()))
+            })
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+
+        let lines = lines
+            .chunks(self.merge_lines.max(1))
+            .map(|chunk| chunk.join(" "))
+            .collect::<Vec<_>>();
+
+        let installments = lines
+            .iter()
+            .filter_map(|line| {
+                let words = line.split_whitespace().collect::<Vec<_>>();
+                if words.len() < self.min_words
+                    || self
+                        .max_words
+                        .is_some_and(|max_words| words.len() > max_words)
+                {
+                    return None;
+                }
+                let date = self.date(&words, locale)?;
+                let optional = |column: Option<usize>| {
+                    column.and_then(|column| self.amount(&words, column, locale))
+                };
+                Some(Installment {
+                    insurance: optional(self.insurance_column).unwrap_or(0.0),
+                    commission: optional(self.commission_column).unwrap_or(0.0),
+                    balance: optional(self.balance_column),
+                    ..Installment::new(
+                        date,
+                        self.amount(&words, self.principal_column, locale)?,
+                        self.amount(&words, self.interest_column, locale)?,
+                    )
+                })
+            })
+            .collect();
+
+        Schedule {
+            loan: LoanInfo::from_header(text, locale),
+            ..Schedule::new(installments)
+        }
+    }
+}
+
+// profiles are described by TOML files in the "profiles" folder next to the executable, the
+// other TOML files there may be configurations of something else
+fn profile_directory() -> Option<PathBuf> {
+    std::env::current_exe().ok().and_then(|executable| {
+        executable
+            .parent()
+            .map(|directory| directory.join("profiles"))
+    })
+}
+
+fn parse_profile(text: &str) -> Result<ParserProfile, String> {
+    let profile = toml::from_str::<ParserProfile>(text).map_err(|error| error.to_string())?;
+    // an empty name would be the beginning of every file name and an empty keyword would be
+    // found in every document
+    if profile.name.trim().is_empty() {
+        return Err("numele profilului lipsește".to_string());
+    }
+    if profile
+        .keywords
+        .iter()
+        .any(|keyword| keyword.trim().is_empty())
+    {
+        return Err("cuvintele cheie nu pot fi goale".to_string());
+    }
+    Ok(profile)
+}
+
+pub(crate) fn load_profiles() -> Vec<ParserProfile> {
+    let Some(directory) = profile_directory() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&directory) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        })
+        .filter_map(|path| {
+            let profile = std::fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| parse_profile(&text));
+            match profile {
+                Ok(profile) => Some(profile),
+                Err(error) => {
+                    eprintln!("Profilul {} a fost ignorat : {}", path.display(), error);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: &str = "date_column = 2\nprincipal_column = 4\ninterest_column = 5\n";
+
+    #[test]
+    fn rejects_profiles_that_would_match_every_document() {
+        let profile = parse_profile(&format!("name = \"bancamea\"\n{}", PROFILE)).unwrap();
+        assert_eq!(profile.name, "bancamea");
+        assert_eq!(
+            parse_profile(&format!("name = \" \"\n{}", PROFILE)).err(),
+            Some("numele profilului lipsește".to_string())
+        );
+        assert_eq!(
+            parse_profile(&format!(
+                "name = \"bancamea\"\nkeywords = [\"Banca Mea\", \"\"]\n{}",
+                PROFILE
+            ))
+            .err(),
+            Some("cuvintele cheie nu pot fi goale".to_string())
+        );
+    }
+}
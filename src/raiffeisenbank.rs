@@ -0,0 +1,82 @@
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::{footer_amounts, PrintedTotals};
+
+pub(crate) fn extract_payment_data(text: &str) -> Schedule {
+    // Nr. | Data scadenta | Rata totala | Principal | Dobanda | Comision administrare | Asigurare | Sold
+    // amounts are printed as "1 234,56", so a number can be split over several words
+    let locale = Locale {
+        space_grouping: true,
+        ..Locale::detect(text)
+    };
+    let installments = text
+        .lines()
+        .filter_map(|line| {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            words.first()?.parse::<usize>().ok()?;
+            let (date, length) = locale.date_prefix(&words[1..])?;
+            let amounts = locale.amounts(&words[1 + length..]);
+            if amounts.len() != 6 {
+                return None;
+            }
+
+            Some(Installment {
+                commission: amounts[3],
+                insurance: amounts[4],
+                balance: Some(amounts[5]),
+                ..Installment::new(date, amounts[1], amounts[2])
+            })
+        })
+        .collect();
+
+    Schedule {
+        loan: LoanInfo::from_header(text, locale),
+        // the total row leaves the balance empty:
+        // Total | Rata totala | Principal | Dobanda | Comision administrare | Asigurare
+        totals: footer_amounts(text, locale).map(|amounts| PrintedTotals {
+            principal: amounts.get(1).copied(),
+            interest: amounts.get(2).copied(),
+            insurance: amounts.get(4).copied(),
+            commission: amounts.get(3).copied(),
+        }),
+        ..Schedule::new(installments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/raiffeisen.txt");
+
+    #[test]
+    fn reads_amounts_grouped_with_spaces() {
+        let schedule = extract_payment_data(FIXTURE);
+        assert_eq!(schedule.installments.len(), 2);
+
+        let first = &schedule.installments[0];
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2023, 7, 12).unwrap());
+        assert_eq!(first.principal, 1050.15);
+        assert_eq!(first.interest, 480.0);
+        assert_eq!(first.commission, 10.0);
+        assert_eq!(first.insurance, 6.91);
+        assert_eq!(first.balance, Some(98949.85));
+        assert!((first.total() - 1547.06).abs() < 1e-6);
+        assert_eq!(schedule.installments[1].balance, Some(97894.45));
+    }
+
+    #[test]
+    fn reads_the_total_row_and_the_header() {
+        let schedule = extract_payment_data(FIXTURE);
+        let totals = schedule.totals.unwrap();
+        assert_eq!(totals.principal, Some(2105.55));
+        assert_eq!(totals.interest, Some(954.75));
+        assert_eq!(totals.commission, Some(20.0));
+        assert_eq!(totals.insurance, Some(13.82));
+        assert_eq!(schedule.loan.contract.as_deref(), Some("RB-2023/4567"));
+        assert_eq!(schedule.loan.amount, Some(100000.0));
+    }
+}
@@ -0,0 +1,432 @@
+use chrono::{Days, NaiveDate};
+
+use crate::arguments::ReconcileArguments;
+use crate::bank::contains_word;
+use crate::schedule::{Installment, Schedule};
+use crate::statementimport::Transaction;
+use crate::table::print_table;
+
+// the words of the usual loan debits, compared without case as whole words; "credit" alone would
+// also match the repayments of credit cards
+const LOAN_KEYWORDS: [&str; 8] = [
+    "rata",
+    "rată",
+    "rambursare anticipata",
+    "rambursare anticipată",
+    "credit ipotecar",
+    "imprumut",
+    "împrumut",
+    "loan",
+];
+
+// differences smaller than a cent come from rounding
+const TOLERANCE: f64 = 0.005;
+
+// a loan debit, as a positive amount
+#[derive(Clone, Copy)]
+struct Payment<'a> {
+    date: NaiveDate,
+    amount: f64,
+    description: &'a str,
+}
+
+// an installment with the debits that belong to it
+struct InstallmentPayments<'a> {
+    installment: &'a Installment,
+    payments: Vec<Payment<'a>>,
+    paid: f64,
+}
+
+// the installments of the period of a statement, with the loan debits matched to them
+struct Reconciliation<'a> {
+    start: NaiveDate,
+    end: NaiveDate,
+    installments: Vec<InstallmentPayments<'a>>,
+    // debits before the window of the last installment due before the statement
+    unmatched: Vec<Payment<'a>>,
+    payment_count: usize,
+}
+
+impl Reconciliation<'_> {
+    // the installments due in the period of the statement
+    fn due(&self) -> impl Iterator<Item = &InstallmentPayments<'_>> {
+        self.installments.iter().filter(|installment| {
+            installment.installment.date >= self.start && installment.installment.date <= self.end
+        })
+    }
+
+    fn arrears(&self) -> f64 {
+        self.due().fold(0.0, |arrears, installment| {
+            arrears + (installment.installment.total() - installment.paid).max(0.0)
+        })
+    }
+
+    // only the payments above an installment are counted, a debit that belongs to no installment
+    // may be for something else
+    fn extra(&self) -> f64 {
+        self.installments.iter().fold(0.0, |extra, installment| {
+            extra + (installment.paid - installment.installment.total()).max(0.0)
+        })
+    }
+}
+
+fn is_loan_payment(transaction: &Transaction, arguments: &ReconcileArguments) -> bool {
+    let description = transaction.description.to_lowercase();
+    transaction.amount < 0.0
+        && match &arguments.description {
+            Some(text) => contains_word(&description, &text.to_lowercase()),
+            None => LOAN_KEYWORDS
+                .iter()
+                .any(|keyword| contains_word(&description, keyword)),
+        }
+}
+
+fn status(
+    installment: &Installment,
+    payments: &[Payment],
+    start: NaiveDate,
+    end: NaiveDate,
+    currency: &str,
+) -> String {
+    let paid = payments.iter().map(|payment| payment.amount).sum::<f64>();
+    let due = installment.total();
+    match payments.first() {
+        None if installment.date > end => "nescadentă".to_string(),
+        // it may have been paid before the statement
+        None if installment.date < start => "scadentă înainte de extras".to_string(),
+        None => "neplătită".to_string(),
+        Some(_) if paid < due - TOLERANCE => "parțială".to_string(),
+        // the installment is paid when the payments reach its total
+        Some(_) => {
+            let mut total = 0.0;
+            let paid_on = payments
+                .iter()
+                .find(|payment| {
+                    total += payment.amount;
+                    total >= due - TOLERANCE
+                })
+                .map(|payment| payment.date)
+                .unwrap_or(installment.date);
+            let status = match (paid_on - installment.date).num_days() {
+                days if days > 0 => format!("întârziată {} zile", days),
+                _ => "plătită".to_string(),
+            };
+            match paid > due + TOLERANCE {
+                true => format!("{}, cu {:.2} {} în plus", status, paid - due, currency),
+                false => status,
+            }
+        }
+    }
+}
+
+// matches the loan debits of the statement to the installments of the period it covers and to
+// the last one due before it: a debit belongs to the installment whose due date follows it by at
+// most `early_days`, or to the latest one due before it
+fn reconcile<'a>(
+    schedule: &'a Schedule,
+    transactions: &'a [Transaction],
+    arguments: &ReconcileArguments,
+) -> Option<Reconciliation<'a>> {
+    let start = transactions
+        .iter()
+        .map(|transaction| transaction.date)
+        .min()?;
+    let end = transactions
+        .iter()
+        .map(|transaction| transaction.date)
+        .max()?;
+    let payments = transactions
+        .iter()
+        .filter(|transaction| is_loan_payment(transaction, arguments))
+        .map(|transaction| Payment {
+            date: transaction.date,
+            amount: -transaction.amount,
+            description: &transaction.description,
+        })
+        .collect::<Vec<_>>();
+
+    let window_start = |installment: &Installment| {
+        installment
+            .date
+            .checked_sub_days(Days::new(arguments.early_days))
+            .unwrap_or(installment.date)
+    };
+    // the last installment due before the statement may be paid late in it
+    let first = schedule
+        .installments
+        .iter()
+        .position(|installment| installment.date >= start)
+        .unwrap_or(schedule.installments.len())
+        .saturating_sub(1);
+    let mut matched = vec![false; payments.len()];
+    let installments = schedule
+        .installments
+        .iter()
+        .enumerate()
+        .skip(first)
+        // the first installment after the statement may be paid in advance
+        .filter(|(_, installment)| window_start(installment) < end)
+        .map(|(index, installment)| {
+            let next = schedule.installments.get(index + 1).map(window_start);
+            let installment_payments = payments
+                .iter()
+                .enumerate()
+                .filter(|(_, payment)| {
+                    payment.date >= window_start(installment)
+                        && next.is_none_or(|next| payment.date < next)
+                })
+                .map(|(position, payment)| {
+                    matched[position] = true;
+                    *payment
+                })
+                .collect::<Vec<_>>();
+            let paid = installment_payments
+                .iter()
+                .fold(0.0, |paid, payment| paid + payment.amount);
+            InstallmentPayments {
+                installment,
+                payments: installment_payments,
+                paid,
+            }
+        })
+        .collect::<Vec<_>>();
+    let unmatched = payments
+        .iter()
+        .zip(&matched)
+        .filter(|(_, matched)| !**matched)
+        .map(|(payment, _)| *payment)
+        .collect::<Vec<_>>();
+
+    Some(Reconciliation {
+        start,
+        end,
+        installments,
+        unmatched,
+        payment_count: payments.len(),
+    })
+}
+
+pub(crate) fn print_reconciliation(
+    schedule: &Schedule,
+    transactions: &[Transaction],
+    arguments: &ReconcileArguments,
+) {
+    let currency = schedule.currency();
+    let Some(reconciliation) = reconcile(schedule, transactions, arguments) else {
+        eprintln!("Extrasul de cont nu conține nicio tranzacție.");
+        return;
+    };
+    let (start, end) = (reconciliation.start, reconciliation.end);
+
+    print_table(
+        &[
+            "Scadență",
+            "Rată",
+            "Plătit",
+            "Data plății",
+            "Diferență",
+            "Stare",
+        ],
+        &reconciliation
+            .installments
+            .iter()
+            .map(|row| {
+                let installment = row.installment;
+                vec![
+                    installment.date.to_string(),
+                    format!("{:.2} {}", installment.total(), currency),
+                    format!("{:.2} {}", row.paid, currency),
+                    row.payments
+                        .iter()
+                        .map(|payment| payment.date.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    format!("{:.2} {}", row.paid - installment.total(), currency),
+                    status(installment, &row.payments, start, end, currency),
+                ]
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    let unmatched = &reconciliation.unmatched;
+    if !unmatched.is_empty() {
+        println!();
+        print_table(
+            &["Dată", "Plată fără rată", "Descriere"],
+            &unmatched
+                .iter()
+                .map(|payment| {
+                    vec![
+                        payment.date.to_string(),
+                        format!("{:.2} {}", payment.amount, currency),
+                        payment.description.to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    let arrears = reconciliation.arrears();
+    let extra = reconciliation.extra();
+    let unmatched_total = unmatched
+        .iter()
+        .fold(0.0, |total, payment| total + payment.amount);
+    let scheduled_balance = schedule
+        .installments
+        .iter()
+        .filter(|installment| installment.date > end)
+        .fold(0.0, |balance, installment| balance + installment.principal);
+
+    println!();
+    println!(
+        "Extras de cont din {} până în {}: {} plăți ale creditului, pentru {} rate scadente.",
+        start,
+        end,
+        reconciliation.payment_count,
+        reconciliation.due().count()
+    );
+    println!("Restanțe: {:.2} {}", arrears, currency);
+    println!("Plăți în plus: {:.2} {}", extra, currency);
+    if !unmatched.is_empty() {
+        println!(
+            "Plăți fără rată: {:.2} {} (nu sunt considerate rambursări anticipate)",
+            unmatched_total, currency
+        );
+    }
+    println!(
+        "Sold conform scadențarului la {}: {:.2} {}",
+        end, scheduled_balance, currency
+    );
+    // the payments above an installment are assumed to go to the principal, the arrears are still
+    // owed
+    println!(
+        "Sold real estimat: {:.2} {}",
+        (scheduled_balance - extra + arrears).max(0.0),
+        currency
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::arguments::Column;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn arguments(description: Option<&str>) -> ReconcileArguments {
+        ReconcileArguments {
+            statement: PathBuf::from("extras.csv"),
+            description: description.map(str::to_string),
+            amount_column: Column::Header("Suma".to_string()),
+            description_column: None,
+            early_days: 10,
+        }
+    }
+
+    fn transaction(date: NaiveDate, amount: f64, description: &str) -> Transaction {
+        Transaction {
+            date,
+            amount,
+            description: description.to_string(),
+        }
+    }
+
+    // installments of 1000 due on the 15th of every month from June to September 2023
+    fn schedule() -> Schedule {
+        Schedule::new(
+            (6..=9)
+                .map(|month| Installment::new(date(2023, month, 15), 900.0, 100.0))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn matches_loan_debits_by_whole_words() {
+        let arguments = arguments(None);
+        let is_loan = |description| {
+            is_loan_payment(
+                &transaction(date(2023, 7, 15), -1000.0, description),
+                &arguments,
+            )
+        };
+        assert!(is_loan("Plata RATA credit"));
+        assert!(is_loan("Rambursare anticipată credit ipotecar"));
+        assert!(!is_loan("Comision cont separata"));
+        assert!(!is_loan("Plata card credit"));
+        assert!(!is_loan_payment(
+            &transaction(date(2023, 7, 15), 1000.0, "Rata"),
+            &arguments
+        ));
+        assert!(!is_loan_payment(
+            &transaction(date(2023, 7, 15), -1000.0, "Transfer RB-2023/45678"),
+            &self::arguments(Some("RB-2023/4567"))
+        ));
+    }
+
+    #[test]
+    fn matches_late_payments_to_the_installment_before_the_statement() {
+        let schedule = schedule();
+        let transactions = [
+            transaction(date(2023, 7, 1), 0.0, "Sold initial"),
+            // the June installment, paid late
+            transaction(date(2023, 7, 3), -1000.0, "Plata rata"),
+            transaction(date(2023, 7, 14), -1000.0, "Plata rata"),
+            transaction(date(2023, 8, 31), 0.0, "Sold final"),
+        ];
+        let reconciliation = reconcile(&schedule, &transactions, &arguments(None)).unwrap();
+        assert_eq!(
+            reconciliation
+                .installments
+                .iter()
+                .map(|installment| (installment.installment.date, installment.paid))
+                .collect::<Vec<_>>(),
+            [
+                (date(2023, 6, 15), 1000.0),
+                (date(2023, 7, 15), 1000.0),
+                (date(2023, 8, 15), 0.0),
+            ]
+        );
+        assert!(reconciliation.unmatched.is_empty());
+        assert_eq!(
+            status(
+                reconciliation.installments[0].installment,
+                &reconciliation.installments[0].payments,
+                reconciliation.start,
+                reconciliation.end,
+                "RON"
+            ),
+            "întârziată 18 zile"
+        );
+        // only the August installment is due in the statement and not paid
+        assert_eq!(reconciliation.due().count(), 2);
+        assert_eq!(reconciliation.arrears(), 1000.0);
+        assert_eq!(reconciliation.extra(), 0.0);
+    }
+
+    #[test]
+    fn does_not_count_unmatched_debits_as_prepayments() {
+        let schedule = schedule();
+        let transactions = [
+            // before the window of the May installment, which is not in the schedule
+            transaction(date(2023, 6, 2), -500.0, "Rata card"),
+            transaction(date(2023, 6, 14), -1200.0, "Plata rata"),
+            transaction(date(2023, 7, 14), -1000.0, "Plata rata"),
+        ];
+        let reconciliation = reconcile(&schedule, &transactions, &arguments(None)).unwrap();
+        assert_eq!(
+            reconciliation
+                .unmatched
+                .iter()
+                .map(|payment| (payment.date, payment.amount))
+                .collect::<Vec<_>>(),
+            [(date(2023, 6, 2), 500.0)]
+        );
+        assert_eq!(reconciliation.payment_count, 3);
+        assert!((reconciliation.extra() - 200.0).abs() < TOLERANCE);
+        assert_eq!(reconciliation.arrears(), 0.0);
+    }
+}
@@ -0,0 +1,211 @@
+use chrono::Months;
+
+use crate::arguments::CompareArguments;
+use crate::schedule::{annual_percentage_rate, annuity_schedule, Installment, Schedule};
+use crate::table::print_table;
+
+pub(crate) fn offer_schedule(offer: &CompareArguments, remaining: &Schedule) -> Schedule {
+    let amount = offer
+        .amount
+        .unwrap_or_else(|| remaining.outstanding_principal());
+    let term = offer
+        .term
+        .unwrap_or(remaining.installments.len() as u32)
+        .max(1);
+    let first_date = remaining
+        .installments
+        .first()
+        .map(|installment| installment.date)
+        .unwrap_or_else(|| {
+            let today = chrono::Local::now().date_naive();
+            today.checked_add_months(Months::new(1)).unwrap_or(today)
+        });
+
+    let mut schedule = annuity_schedule(amount, offer.rate / 100.0, term, first_date);
+    schedule.installments.iter_mut().for_each(|installment| {
+        installment.commission = offer.monthly_fee;
+        installment.insurance = offer.insurance;
+    });
+    schedule
+}
+
+// the month after which the offer stays cheaper: the last one in which the cumulative difference
+// becomes positive, none when the offer costs more in the end
+fn break_even_month(cumulative_differences: &[f64]) -> Option<usize> {
+    match cumulative_differences.last() {
+        Some(total) if *total >= 0.0 => Some(
+            cumulative_differences
+                .iter()
+                .rposition(|difference| *difference < 0.0)
+                .map_or(1, |month| month + 2),
+        ),
+        _ => None,
+    }
+}
+
+pub(crate) fn print_refinance_comparison(schedule: &Schedule, offer: &CompareArguments) {
+    // the remaining installments and the offer keep the currency of the loan
+    let currency = schedule.currency();
+    let from = offer
+        .from
+        .unwrap_or_else(|| chrono::Local::now().date_naive());
+    let remaining = Schedule::new(
+        schedule
+            .installments
+            .iter()
+            .filter(|installment| installment.date > from)
+            .cloned()
+            .collect(),
+    );
+    let alternative = offer_schedule(offer, &remaining);
+    let one_off_costs = offer.valuation + offer.notary + offer.other_costs + offer.exit_fee;
+
+    let mut cumulative_difference = -one_off_costs;
+    let mut cumulative_differences = Vec::new();
+    let months = remaining
+        .installments
+        .len()
+        .max(alternative.installments.len());
+    let rows = (0..months)
+        .map(|month| {
+            let current = remaining.installments.get(month);
+            let proposed = alternative.installments.get(month);
+            let current_total = current.map(Installment::total).unwrap_or(0.0);
+            let proposed_total = proposed.map(Installment::total).unwrap_or(0.0);
+            let difference = current_total - proposed_total;
+            cumulative_difference += difference;
+            cumulative_differences.push(cumulative_difference);
+            vec![
+                (month + 1).to_string(),
+                current
+                    .or(proposed)
+                    .map(|installment| installment.date.to_string())
+                    .unwrap_or_default(),
+                format!("{:.2} {}", current_total, currency),
+                format!("{:.2} {}", proposed_total, currency),
+                format!("{:.2} {}", difference, currency),
+                format!("{:.2} {}", cumulative_difference, currency),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    print_table(
+        &[
+            "Număr",
+            "Dată",
+            "Rată actuală",
+            "Rată ofertă",
+            "Diferență",
+            "Diferență cumulată",
+        ],
+        &rows,
+    );
+
+    let summary = |name: &str, schedule: &Schedule, amount_received: f64, upfront: f64| {
+        let interest = schedule
+            .installments
+            .iter()
+            .map(|installment| installment.interest)
+            .sum::<f64>();
+        let fees = schedule
+            .installments
+            .iter()
+            .map(|installment| installment.insurance + installment.commission)
+            .sum::<f64>();
+        let payments = schedule
+            .installments
+            .iter()
+            .map(Installment::total)
+            .collect::<Vec<_>>();
+        vec![
+            name.to_string(),
+            format!("{:.2} {}", schedule.outstanding_principal(), currency),
+            schedule.installments.len().to_string(),
+            format!("{:.2} {}", interest, currency),
+            format!("{:.2} {}", fees, currency),
+            format!("{:.2} {}", upfront, currency),
+            format!("{:.2} {}", interest + fees + upfront, currency),
+            // left empty when the costs leave nothing received
+            annual_percentage_rate(amount_received, &payments)
+                .map(|rate| format!("{:.2}%", rate * 100.0))
+                .unwrap_or_default(),
+        ]
+    };
+    let current_balance = remaining.outstanding_principal();
+    let alternative_amount = alternative.outstanding_principal();
+
+    println!();
+    print_table(
+        &[
+            "Credit",
+            "Capital",
+            "Rate",
+            "Dobândă",
+            "Comisioane și asigurări",
+            "Costuri inițiale",
+            "Cost total",
+            "DAE",
+        ],
+        &[
+            summary("Actual", &remaining, current_balance, 0.0),
+            summary(
+                "Ofertă",
+                &alternative,
+                alternative_amount - one_off_costs,
+                one_off_costs,
+            ),
+        ],
+    );
+
+    match break_even_month(&cumulative_differences) {
+        Some(month) => println!(
+            "Refinanțarea devine avantajoasă după {} luni (economie totală {:.2} {}).",
+            month, cumulative_difference, currency
+        ),
+        None => println!(
+            "Refinanțarea nu devine avantajoasă (pierdere totală {:.2} {}).",
+            -cumulative_difference, currency
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn breaks_even_when_the_offer_stays_cheaper() {
+        assert_eq!(break_even_month(&[-100.0, -20.0, 30.0, 80.0]), Some(3));
+        assert_eq!(break_even_month(&[10.0, 20.0]), Some(1));
+        // cheaper at first, then dearer, then cheaper again
+        assert_eq!(break_even_month(&[10.0, -5.0, 3.0]), Some(3));
+        assert_eq!(break_even_month(&[10.0, 20.0, -1.0]), None);
+        assert_eq!(break_even_month(&[]), None);
+    }
+
+    #[test]
+    fn a_longer_offer_with_a_higher_rate_never_breaks_even() {
+        let first_date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let current = annuity_schedule(10000.0, 0.08, 12, first_date);
+        let offer = annuity_schedule(10000.0, 0.15, 36, first_date);
+        let cumulative_differences = (0..36)
+            .scan(0.0, |cumulative, month| {
+                let total = |schedule: &Schedule| {
+                    schedule
+                        .installments
+                        .get(month)
+                        .map(Installment::total)
+                        .unwrap_or(0.0)
+                };
+                *cumulative += total(&current) - total(&offer);
+                Some(*cumulative)
+            })
+            .collect::<Vec<_>>();
+        // the smaller installments of the offer are cheaper during the first year
+        assert!(cumulative_differences[0] > 0.0);
+        assert!(cumulative_differences[35] < 0.0);
+        assert_eq!(break_even_month(&cumulative_differences), None);
+    }
+}
@@ -0,0 +1,231 @@
+use chrono::{Months, NaiveDate};
+
+use crate::loaninfo::LoanInfo;
+use crate::totals::PrintedTotals;
+
+pub(crate) type PaymentData = (
+    Vec<(NaiveDate, f64, f64, f64, f64, f64, f64)>,
+    Vec<usize>,
+    Vec<usize>,
+);
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Installment {
+    pub(crate) date: NaiveDate,
+    pub(crate) principal: f64,
+    pub(crate) interest: f64,
+    pub(crate) insurance: f64,
+    pub(crate) commission: f64,
+    // principal left after the installment, when the schedule prints it
+    pub(crate) balance: Option<f64>,
+}
+
+impl Installment {
+    pub(crate) fn new(date: NaiveDate, principal: f64, interest: f64) -> Installment {
+        Installment {
+            date,
+            principal,
+            interest,
+            insurance: 0.0,
+            commission: 0.0,
+            balance: None,
+        }
+    }
+
+    // everything paid on top of the principal
+    pub(crate) fn cost(&self) -> f64 {
+        self.interest + self.insurance + self.commission
+    }
+
+    pub(crate) fn total(&self) -> f64 {
+        self.principal + self.cost()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Schedule {
+    pub(crate) installments: Vec<Installment>,
+    pub(crate) loan: LoanInfo,
+    // the "Total" row of the bank's schedule, to check that no row was lost
+    pub(crate) totals: Option<PrintedTotals>,
+}
+
+impl Schedule {
+    pub(crate) fn new(installments: Vec<Installment>) -> Schedule {
+        Schedule {
+            installments,
+            loan: LoanInfo::default(),
+            totals: None,
+        }
+    }
+
+    // the currency of the amounts, RON when the schedule does not name one
+    pub(crate) fn currency(&self) -> &str {
+        self.loan.currency.as_deref().unwrap_or("RON")
+    }
+
+    pub(crate) fn outstanding_principal(&self) -> f64 {
+        self.installments
+            .iter()
+            .map(|installment| installment.principal)
+            .sum()
+    }
+
+    // principal still owed right before each installment is paid
+    pub(crate) fn balances_before(&self) -> Vec<f64> {
+        let mut balance = self.outstanding_principal();
+        self.installments
+            .iter()
+            .map(|installment| {
+                let balance_before = match installment.balance {
+                    Some(balance_after) => balance_after + installment.principal,
+                    None => balance,
+                };
+                balance = balance_before - installment.principal;
+                balance_before
+            })
+            .collect()
+    }
+
+    pub(crate) fn payment_data(&self) -> PaymentData {
+        let mut total_principal = 0.0;
+        let mut total_interest = 0.0;
+
+        let mut max_local_performance = 0.0;
+        let mut max_local_performance_indexes = Vec::new();
+        let mut max_global_performance = 0.0;
+        let mut max_global_performance_indexes = Vec::new();
+
+        let payment_data = self
+            .installments
+            .iter()
+            .enumerate()
+            .map(|(i, installment)| {
+                let local_principal = installment.principal;
+                let local_interest = installment.cost();
+                let local_performance = local_interest / local_principal * 100.0;
+                if local_performance > max_local_performance {
+                    max_local_performance = local_performance;
+                    max_local_performance_indexes.clear();
+                    max_local_performance_indexes.push(i);
+                } else if local_performance == max_local_performance {
+                    max_local_performance_indexes.push(i);
+                }
+
+                total_principal += local_principal;
+                total_interest += local_interest;
+                let total_performance = total_interest / total_principal * 100.0;
+                if total_performance > max_global_performance {
+                    max_global_performance = total_performance;
+                    max_global_performance_indexes.clear();
+                    max_global_performance_indexes.push(i);
+                } else if total_performance == max_global_performance {
+                    max_global_performance_indexes.push(i);
+                }
+
+                (
+                    installment.date,
+                    local_principal,
+                    local_interest,
+                    local_performance,
+                    total_principal,
+                    total_interest,
+                    total_performance,
+                )
+            })
+            .collect();
+
+        (
+            payment_data,
+            max_local_performance_indexes,
+            max_global_performance_indexes,
+        )
+    }
+}
+
+// equal monthly installments (principal + interest), the first one due on `first_date`
+pub(crate) fn annuity_schedule(
+    amount: f64,
+    annual_rate: f64,
+    term: u32,
+    first_date: NaiveDate,
+) -> Schedule {
+    let monthly_rate = annual_rate / 12.0;
+    let payment = match monthly_rate == 0.0 {
+        true => amount / term as f64,
+        false => amount * monthly_rate / (1.0 - (1.0 + monthly_rate).powi(-(term as i32))),
+    };
+
+    let mut balance = amount;
+    let installments = (0..term)
+        .map(|month| {
+            let date = first_date
+                .checked_add_months(Months::new(month))
+                .unwrap_or(first_date);
+            let interest = balance * monthly_rate;
+            let principal = match month + 1 == term {
+                true => balance,
+                false => payment - interest,
+            };
+            balance -= principal;
+            Installment::new(date, principal, interest)
+        })
+        .collect();
+
+    Schedule::new(installments)
+}
+
+// the yearly rate that discounts every payment back to the amount received (DAE); none when no
+// rate between -99% and 100% a month does, as when nothing is received or nothing is paid back
+pub(crate) fn annual_percentage_rate(
+    amount_received: f64,
+    monthly_payments: &[f64],
+) -> Option<f64> {
+    let present_value = |monthly_rate: f64| {
+        monthly_payments
+            .iter()
+            .enumerate()
+            .map(|(month, payment)| payment / (1.0 + monthly_rate).powi(month as i32 + 1))
+            .sum::<f64>()
+    };
+
+    let (mut low, mut high) = (-0.99, 1.0);
+    if amount_received <= 0.0
+        || present_value(low) <= amount_received
+        || present_value(high) >= amount_received
+    {
+        return None;
+    }
+    for _ in 0..200 {
+        let middle = (low + high) / 2.0;
+        if present_value(middle) > amount_received {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some((1.0 + (low + high) / 2.0).powi(12) - 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annual_percentage_rate_of_an_annuity_compounds_the_monthly_rate() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let payments = annuity_schedule(10000.0, 0.12, 12, date)
+            .installments
+            .iter()
+            .map(Installment::total)
+            .collect::<Vec<_>>();
+        let rate = annual_percentage_rate(10000.0, &payments).unwrap();
+        assert!((rate - (1.01f64.powi(12) - 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn annual_percentage_rate_needs_an_amount_and_payments() {
+        assert_eq!(annual_percentage_rate(0.0, &[100.0, 100.0]), None);
+        assert_eq!(annual_percentage_rate(1000.0, &[]), None);
+    }
+}
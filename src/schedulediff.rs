@@ -0,0 +1,156 @@
+use chrono::Datelike;
+
+use crate::schedule::{Installment, Schedule};
+use crate::table::print_table;
+
+// differences smaller than a cent come from rounding
+const TOLERANCE: f64 = 0.005;
+
+enum Change<'a> {
+    Changed(&'a Installment, &'a Installment),
+    Removed(&'a Installment),
+    Added(&'a Installment),
+}
+
+// installments are matched by the month they are due in, banks move due dates falling on
+// holidays from one revision to another
+fn align<'a>(old: &'a [Installment], new: &'a [Installment]) -> Vec<Change<'a>> {
+    let month = |installment: &Installment| (installment.date.year(), installment.date.month());
+    let (mut old, mut new) = (old.iter().peekable(), new.iter().peekable());
+    let mut changes = Vec::new();
+    loop {
+        match (old.peek(), new.peek()) {
+            (Some(&previous), Some(&current)) if month(previous) == month(current) => {
+                changes.push(Change::Changed(previous, current));
+                old.next();
+                new.next();
+            }
+            (Some(&previous), Some(&current)) if month(previous) < month(current) => {
+                changes.push(Change::Removed(previous));
+                old.next();
+            }
+            (_, Some(&current)) => {
+                changes.push(Change::Added(current));
+                new.next();
+            }
+            (Some(&previous), None) => {
+                changes.push(Change::Removed(previous));
+                old.next();
+            }
+            (None, None) => return changes,
+        }
+    }
+}
+
+fn amount(amount: Option<f64>, currency: &str) -> String {
+    amount
+        .map(|amount| format!("{:.2} {}", amount, currency))
+        .unwrap_or_default()
+}
+
+fn sum(installments: &[Installment], amount: fn(&Installment) -> f64) -> f64 {
+    installments.iter().map(amount).sum()
+}
+
+// compares a new revision of the schedule with the old one, from the first installment of the
+// new one: the old installments before it were already paid
+pub(crate) fn print_schedule_diff(old: &Schedule, new: &Schedule) {
+    let currency = new.currency();
+    let Some(start) = new.installments.first().map(|installment| installment.date) else {
+        eprintln!("Scadențarul nou nu conține nicio rată.");
+        return;
+    };
+    let remaining = old
+        .installments
+        .iter()
+        .filter(|installment| {
+            (installment.date.year(), installment.date.month()) >= (start.year(), start.month())
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let changes = align(&remaining, &new.installments);
+
+    let rows = changes
+        .iter()
+        .filter_map(|change| {
+            let (date, old, new, status) = match change {
+                Change::Changed(old, new) => {
+                    let changed = (old.principal - new.principal).abs() > TOLERANCE
+                        || (old.interest - new.interest).abs() > TOLERANCE
+                        || (old.total() - new.total()).abs() > TOLERANCE;
+                    if !changed && old.date == new.date {
+                        return None;
+                    }
+                    (new.date, Some(*old), Some(*new), "modificată")
+                }
+                Change::Removed(old) => (old.date, Some(*old), None, "eliminată"),
+                Change::Added(new) => (new.date, None, Some(*new), "nouă"),
+            };
+            let total = |installment: Option<&Installment>| {
+                installment.map(Installment::total).unwrap_or(0.0)
+            };
+            Some(vec![
+                date.to_string(),
+                amount(old.map(|installment| installment.principal), currency),
+                amount(new.map(|installment| installment.principal), currency),
+                amount(old.map(|installment| installment.interest), currency),
+                amount(new.map(|installment| installment.interest), currency),
+                format!("{:.2} {}", total(new) - total(old), currency),
+                status.to_string(),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    if rows.is_empty() {
+        println!("Ratele rămase sunt aceleași în ambele scadențare.");
+    } else {
+        print_table(
+            &[
+                "Dată",
+                "Capital vechi",
+                "Capital nou",
+                "Dobândă veche",
+                "Dobândă nouă",
+                "Diferență rată",
+                "Stare",
+            ],
+            &rows,
+        );
+    }
+
+    let old_principal = sum(&remaining, |installment| installment.principal);
+    let new_principal = sum(&new.installments, |installment| installment.principal);
+    let old_interest = sum(&remaining, |installment| installment.interest);
+    let new_interest = sum(&new.installments, |installment| installment.interest);
+
+    println!();
+    println!(
+        "Rate rămase: {} în scadențarul vechi, {} în cel nou.",
+        remaining.len(),
+        new.installments.len()
+    );
+    if let (Some(old_end), Some(new_end)) = (
+        remaining.last().map(|installment| installment.date),
+        new.installments.last().map(|installment| installment.date),
+    ) {
+        println!("Ultima rată: {} în loc de {}.", new_end, old_end);
+    }
+    println!(
+        "Capital rămas: {:.2} {} în loc de {:.2} {} (diferență {:.2} {}).",
+        new_principal,
+        currency,
+        old_principal,
+        currency,
+        new_principal - old_principal,
+        currency
+    );
+    println!(
+        "Dobândă rămasă: {:.2} {} în loc de {:.2} {} (diferență {:.2} {}).",
+        new_interest,
+        currency,
+        old_interest,
+        currency,
+        new_interest - old_interest,
+        currency
+    );
+}
@@ -0,0 +1,309 @@
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::daycount::DayCount;
+use crate::loaninfo::LoanInfo;
+use crate::prepayment::accrual_periods;
+use crate::schedule::{Installment, Schedule};
+
+// bumped on every change that older versions of the tool cannot read
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ScheduleDocument {
+    pub(crate) version: u32,
+    #[serde(default)]
+    pub(crate) loan: LoanMetadata,
+    #[serde(default = "default_currency")]
+    pub(crate) currency: String,
+    #[serde(default)]
+    pub(crate) rate_periods: Vec<RatePeriod>,
+    pub(crate) installments: Vec<InstallmentRecord>,
+    #[serde(default)]
+    pub(crate) provenance: Provenance,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct LoanMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) amount: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) first_due_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) last_due_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) contract: Option<String>,
+    // the amount in the contract, `amount` is the principal left in the schedule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) granted_amount: Option<f64>,
+    // yearly percentages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) annual_rate: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) margin: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) reference_index: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) start_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) term_months: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RatePeriod {
+    pub(crate) from: NaiveDate,
+    pub(crate) to: NaiveDate,
+    // yearly percentage
+    pub(crate) annual_rate: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct InstallmentRecord {
+    pub(crate) number: usize,
+    pub(crate) date: NaiveDate,
+    pub(crate) principal: f64,
+    pub(crate) interest: f64,
+    #[serde(default)]
+    pub(crate) insurance: f64,
+    #[serde(default)]
+    pub(crate) commission: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) balance: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct Provenance {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) bank: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) generated_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) created: Option<NaiveDate>,
+}
+
+fn default_currency() -> String {
+    "RON".to_string()
+}
+
+// consecutive installments charged the same rate, rounded to a hundredth of a percent; most
+// schedules charge a twelfth of the yearly rate every month, which 30/360 reproduces exactly
+fn rate_periods(schedule: &Schedule) -> Vec<RatePeriod> {
+    let mut rate_periods: Vec<RatePeriod> = Vec::new();
+    accrual_periods(schedule, DayCount::Thirty360)
+        .iter()
+        .for_each(|period| {
+            let annual_rate = (period.annual_rate * 10000.0).round() / 100.0;
+            match rate_periods.last_mut() {
+                Some(last) if last.annual_rate == annual_rate => last.to = period.end,
+                _ => rate_periods.push(RatePeriod {
+                    from: period.start,
+                    to: period.end,
+                    annual_rate,
+                }),
+            }
+        });
+    rate_periods
+}
+
+pub(crate) fn to_document(
+    schedule: &Schedule,
+    source: Option<&Path>,
+    bank: Option<String>,
+) -> ScheduleDocument {
+    ScheduleDocument {
+        version: FORMAT_VERSION,
+        loan: LoanMetadata {
+            amount: Some(schedule.outstanding_principal()),
+            first_due_date: schedule
+                .installments
+                .first()
+                .map(|installment| installment.date),
+            last_due_date: schedule
+                .installments
+                .last()
+                .map(|installment| installment.date),
+            contract: schedule.loan.contract.clone(),
+            granted_amount: schedule.loan.amount,
+            annual_rate: schedule.loan.annual_rate,
+            margin: schedule.loan.margin,
+            reference_index: schedule.loan.reference_index.clone(),
+            start_date: schedule.loan.start_date,
+            term_months: schedule.loan.term,
+        },
+        currency: schedule
+            .loan
+            .currency
+            .clone()
+            .unwrap_or_else(default_currency),
+        rate_periods: rate_periods(schedule),
+        installments: schedule
+            .installments
+            .iter()
+            .enumerate()
+            .map(|(index, installment)| InstallmentRecord {
+                number: index + 1,
+                date: installment.date,
+                principal: installment.principal,
+                interest: installment.interest,
+                insurance: installment.insurance,
+                commission: installment.commission,
+                balance: installment.balance,
+            })
+            .collect(),
+        provenance: Provenance {
+            source: source
+                .and_then(|source| source.file_name())
+                .map(|file_name| file_name.to_string_lossy().to_string()),
+            bank,
+            generated_by: Some(format!(
+                "{} {}",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            )),
+            created: Some(chrono::Local::now().date_naive()),
+        },
+    }
+}
+
+pub(crate) fn to_schedule(document: &ScheduleDocument) -> Schedule {
+    let loan = &document.loan;
+    Schedule {
+        loan: LoanInfo {
+            contract: loan.contract.clone(),
+            amount: loan.granted_amount,
+            currency: Some(document.currency.clone()),
+            annual_rate: loan.annual_rate,
+            margin: loan.margin,
+            reference_index: loan.reference_index.clone(),
+            start_date: loan.start_date,
+            term: loan.term_months,
+        },
+        ..Schedule::new(
+            document
+                .installments
+                .iter()
+                .map(|record| Installment {
+                    insurance: record.insurance,
+                    commission: record.commission,
+                    balance: record.balance,
+                    ..Installment::new(record.date, record.principal, record.interest)
+                })
+                .collect(),
+        )
+    }
+}
+
+pub(crate) fn read_document(json_file: &PathBuf) -> Result<ScheduleDocument, String> {
+    let json = std::fs::read_to_string(json_file)
+        .map_err(|error| format!("Fișierul JSON nu a putut fi citit : {}", error))?;
+    parse_document(&json)
+}
+
+pub(crate) fn parse_document(json: &str) -> Result<ScheduleDocument, String> {
+    let document: ScheduleDocument = serde_json::from_str(json)
+        .map_err(|error| format!("Textul JSON nu este un scadențar valid : {}", error))?;
+    // the versions start from 1, a 0 is a version that never existed
+    if !(1..=FORMAT_VERSION).contains(&document.version) {
+        return Err(format!(
+            "Scadențarul are versiunea {}, dar programul cunoaște doar versiunile de la 1 la {}.",
+            document.version, FORMAT_VERSION
+        ));
+    }
+    Ok(document)
+}
+
+pub(crate) fn write_document(document: &ScheduleDocument, json_file: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(document)
+        .map_err(|error| format!("Scadențarul nu a putut fi convertit în JSON : {}", error))?;
+    std::fs::write(json_file, json).map_err(|error| {
+        format!(
+            "Fișierul {} nu a putut fi scris : {}",
+            json_file.display(),
+            error
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> Schedule {
+        let mut schedule = crate::schedule::annuity_schedule(
+            10000.0,
+            0.06,
+            3,
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        );
+        schedule.installments[1].insurance = 5.5;
+        schedule.installments[2].commission = 10.0;
+        schedule.installments[2].balance = Some(0.0);
+        schedule.loan = LoanInfo {
+            contract: Some("4471/2024".to_string()),
+            amount: Some(10000.0),
+            currency: Some("EUR".to_string()),
+            annual_rate: Some(6.0),
+            start_date: NaiveDate::from_ymd_opt(2023, 12, 15),
+            term: Some(3),
+            ..LoanInfo::default()
+        };
+        schedule
+    }
+
+    #[test]
+    fn reads_back_the_exported_schedule() {
+        let schedule = schedule();
+        let document = to_document(&schedule, Some(Path::new("scadentar.pdf")), None);
+        let json = serde_json::to_string_pretty(&document).unwrap();
+        let imported = to_schedule(&parse_document(&json).unwrap());
+
+        let fields = |schedule: &Schedule| {
+            schedule
+                .installments
+                .iter()
+                .map(|installment| {
+                    (
+                        installment.date,
+                        installment.principal,
+                        installment.interest,
+                        installment.insurance,
+                        installment.commission,
+                        installment.balance,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fields(&imported), fields(&schedule));
+        assert_eq!(imported.loan.contract, schedule.loan.contract);
+        assert_eq!(imported.loan.amount, schedule.loan.amount);
+        assert_eq!(imported.currency(), "EUR");
+        assert_eq!(imported.loan.annual_rate, Some(6.0));
+        assert_eq!(imported.loan.start_date, schedule.loan.start_date);
+        assert_eq!(imported.loan.term, Some(3));
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let json = |version: &str| {
+            format!(
+                "{{{}\"installments\": [{{\"number\": 1, \"date\": \"2024-01-15\", \"principal\": 100.0, \"interest\": 5.0}}]}}",
+                version
+            )
+        };
+        assert!(parse_document(&json("\"version\": 1, ")).is_ok());
+        assert_eq!(
+            parse_document(&json("\"version\": 0, ")).err().as_deref(),
+            Some(
+                "Scadențarul are versiunea 0, dar programul cunoaște doar versiunile de la 1 la 1."
+            )
+        );
+        assert!(parse_document(&json("\"version\": 2, ")).is_err());
+        assert!(parse_document(&json(""))
+            .err()
+            .is_some_and(|error| error.contains("missing field `version`")));
+    }
+}
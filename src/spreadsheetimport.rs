@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use calamine::{open_workbook_auto, Data, DataType, Reader};
+use chrono::NaiveDate;
+
+use crate::arguments::ImportArguments;
+use crate::csvimport::{detect_locale, parse_date, text_amount, ColumnMapping};
+use crate::locale::Locale;
+use crate::schedule::Schedule;
+
+fn cell_text(cell: &Data) -> String {
+    cell.as_string().unwrap_or_default().trim().to_string()
+}
+
+fn cell_date(cell: &Data, date_format: Option<&str>, locale: Locale) -> Option<NaiveDate> {
+    match cell {
+        Data::String(text) => parse_date(text, date_format, locale),
+        Data::DateTime(_) | Data::DateTimeIso(_) => cell.as_date(),
+        _ => None,
+    }
+}
+
+// none for an empty cell, the text of a cell that is not an amount
+fn cell_amount(cell: &Data, locale: Locale) -> Result<Option<f64>, String> {
+    match cell {
+        Data::Int(value) => Ok(Some(*value as f64)),
+        Data::Float(value) => Ok(Some(*value)),
+        Data::String(text) => text_amount(text, locale),
+        Data::Empty => Ok(None),
+        _ => Err(cell.to_string()),
+    }
+}
+
+pub(crate) fn import_schedule(
+    spreadsheet_file: &PathBuf,
+    arguments: &ImportArguments,
+) -> Result<Schedule, String> {
+    let mut workbook = open_workbook_auto(spreadsheet_file)
+        .map_err(|error| format!("Foaia de calcul nu a putut fi citită : {}", error))?;
+    let sheet_names = workbook.sheet_names();
+    let sheet_name = match &arguments.sheet {
+        None => sheet_names.first().cloned(),
+        Some(sheet) => match sheet.parse::<usize>() {
+            Ok(index) => sheet_names.get(index.saturating_sub(1)).cloned(),
+            Err(_) => sheet_names.iter().find(|name| *name == sheet).cloned(),
+        },
+    }
+    .ok_or_else(|| {
+        format!(
+            "Foaia de calcul nu există. Foile disponibile sunt : {}",
+            sheet_names.join(", ")
+        )
+    })?;
+    let range = workbook
+        .worksheet_range(&sheet_name)
+        .map_err(|error| format!("Foaia \"{}\" nu a putut fi citită : {}", sheet_name, error))?;
+    let range = match arguments.range {
+        Some((start, end)) => range.range(start, end),
+        None => range,
+    };
+    let rows = range.rows().collect::<Vec<_>>();
+
+    // the header is the first row where every column given by name is found
+    let (header_row, columns) = match ColumnMapping::uses_headers(arguments) {
+        true => rows
+            .iter()
+            .enumerate()
+            .find_map(|(index, row)| {
+                let headers = row.iter().map(cell_text).collect::<Vec<_>>();
+                ColumnMapping::new(arguments, &headers)
+                    .ok()
+                    .map(|columns| (Some(index), columns))
+            })
+            .map_or_else(
+                || ColumnMapping::new(arguments, &[]).map(|columns| (None, columns)),
+                Ok,
+            )?,
+        false => (None, ColumnMapping::new(arguments, &[])?),
+    };
+
+    let locale = detect_locale(
+        rows.iter()
+            .flat_map(|row| row.iter())
+            .filter_map(|cell| match cell {
+                Data::String(text) => Some(text.as_str()),
+                _ => None,
+            }),
+        arguments.csv_decimal_separator,
+    );
+    let date_format = arguments.csv_date_format.as_deref();
+    // the rows are numbered as in the spreadsheet
+    let first_row = range.start().map_or(1, |(row, _)| row as u64 + 1);
+    let installments = rows
+        .iter()
+        .enumerate()
+        .skip(header_row.map(|index| index + 1).unwrap_or(0))
+        .filter_map(|(index, row)| {
+            columns.installment(
+                first_row + index as u64,
+                |column| cell_date(row.get(column)?, date_format, locale),
+                |column| {
+                    row.get(column)
+                        .map_or(Ok(None), |cell| cell_amount(cell, locale))
+                },
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| format!("Foaia \"{}\" nu a putut fi citită : {}", sheet_name, error))?;
+
+    if installments.is_empty() {
+        return Err(format!(
+            "Foaia \"{}\" nu conține nicio rată cu data validă.",
+            sheet_name
+        ));
+    }
+    Ok(Schedule::new(installments))
+}
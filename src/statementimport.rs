@@ -0,0 +1,356 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crate::arguments::{ImportArguments, ReconcileArguments};
+use crate::bank::contains_word;
+use crate::csvimport::{column_index, detect_locale, header_key, parse_date};
+
+// the usual names of the columns in internet banking exports, compared as `header_key` returns them
+const DATE_HEADERS: [&str; 7] = [
+    "data",
+    "data tranzactiei",
+    "data tranzactie",
+    "data operatiunii",
+    "data inregistrarii",
+    "date",
+    "transaction date",
+];
+const AMOUNT_HEADERS: [&str; 6] = [
+    "suma",
+    "suma tranzactiei",
+    "valoare",
+    "debit",
+    "suma debit",
+    "amount",
+];
+const CREDIT_HEADERS: [&str; 3] = ["credit", "suma credit", "incasari"];
+const DESCRIPTION_HEADERS: [&str; 6] = [
+    "descriere",
+    "detalii",
+    "detalii tranzactie",
+    "explicatii",
+    "description",
+    "details",
+];
+
+// a transaction from an account statement, debits are negative
+pub(crate) struct Transaction {
+    pub(crate) date: NaiveDate,
+    pub(crate) amount: f64,
+    pub(crate) description: String,
+}
+
+fn import_csv(
+    csv_file: &Path,
+    import: &ImportArguments,
+    arguments: &ReconcileArguments,
+) -> Result<Vec<Transaction>, String> {
+    let file = File::open(csv_file)
+        .map_err(|error| format!("Fișierul CSV nu a putut fi citit : {}", error))?;
+    read_csv(file, import, arguments)
+}
+
+fn read_csv(
+    csv: impl Read,
+    import: &ImportArguments,
+    arguments: &ReconcileArguments,
+) -> Result<Vec<Transaction>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(import.csv_delimiter as u8)
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(csv);
+    let headers = reader
+        .headers()
+        .map_err(|error| format!("Fișierul CSV nu a putut fi citit : {}", error))?
+        .iter()
+        .map(|header| header.to_string())
+        .collect::<Vec<_>>();
+
+    let date_column = column_index(&import.csv_date_column, &headers, &DATE_HEADERS)?;
+    let amount_column = column_index(&arguments.amount_column, &headers, &AMOUNT_HEADERS)?;
+    let description_column = match &arguments.description_column {
+        Some(column) => Some(column_index(column, &headers, &DESCRIPTION_HEADERS)?),
+        None => headers
+            .iter()
+            .position(|header| DESCRIPTION_HEADERS.contains(&header_key(header).as_str())),
+    };
+    // the exports with a debit column have the credits in a column of their own, both without
+    // signs; the other exports have signed amounts
+    let debits = headers
+        .get(amount_column)
+        .is_some_and(|header| contains_word(&header_key(header), "debit"));
+    let credit_column = debits
+        .then(|| {
+            headers
+                .iter()
+                .position(|header| CREDIT_HEADERS.contains(&header_key(header).as_str()))
+        })
+        .flatten();
+
+    let records = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| match error.position() {
+            Some(position) => format!(
+                "Rândul {} din fișierul CSV nu a putut fi citit : {}",
+                position.line(),
+                error
+            ),
+            None => format!("Fișierul CSV nu a putut fi citit : {}", error),
+        })?;
+    let locale = detect_locale(
+        records.iter().flat_map(|record| record.iter()),
+        import.csv_decimal_separator,
+    );
+    let date_format = import.csv_date_format.as_deref();
+    let amount = |record: &csv::StringRecord, column: usize| {
+        record
+            .get(column)
+            .and_then(|text| locale.parse_amount(text))
+    };
+    Ok(records
+        .iter()
+        // rows without a valid date are balances, notes or totals
+        .filter_map(|record| {
+            Some(Transaction {
+                date: parse_date(record.get(date_column)?, date_format, locale)?,
+                amount: match debits {
+                    // a row has either a debit or a credit, the other one is empty or zero
+                    true => amount(record, amount_column)
+                        .filter(|debit| *debit != 0.0)
+                        .map(|debit| -debit.abs())
+                        .or_else(|| amount(record, credit_column?).map(f64::abs))
+                        .or_else(|| amount(record, amount_column))?,
+                    false => amount(record, amount_column)?,
+                },
+                description: description_column
+                    .and_then(|column| record.get(column))
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        })
+        .collect())
+}
+
+// the value of a :61: field: value date (YYMMDD), optional entry date (MMDD), debit/credit mark,
+// optional funds code and the amount with a decimal comma
+fn mt940_amount(value: &str) -> Option<(NaiveDate, f64)> {
+    let date = NaiveDate::parse_from_str(value.get(..6)?, "%y%m%d").ok()?;
+    let rest = value
+        .get(6..)?
+        .trim_start_matches(|c: char| c.is_ascii_digit());
+    // reversals of credits are debits and the other way around
+    let (sign, rest) = [("RC", -1.0), ("RD", 1.0), ("D", -1.0), ("C", 1.0)]
+        .iter()
+        .find_map(|(mark, sign)| rest.strip_prefix(mark).map(|rest| (*sign, rest)))?;
+    let amount = rest
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == ',')
+        .collect::<String>()
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()?;
+    Some((date, sign * amount))
+}
+
+fn parse_mt940(text: &str) -> Result<Vec<Transaction>, String> {
+    // the fields start with a tag such as :61: or :28C:, the lines without one continue the field
+    let mut fields: Vec<(String, String)> = Vec::new();
+    text.lines().for_each(|line| {
+        let tag = line
+            .strip_prefix(':')
+            .and_then(|rest| rest.split_once(':'))
+            .filter(|(tag, _)| {
+                (2..=3).contains(&tag.len()) && tag.starts_with(|c: char| c.is_ascii_digit())
+            });
+        match (tag, fields.last_mut()) {
+            (Some((tag, value)), _) => fields.push((tag.to_string(), value.to_string())),
+            (None, Some((_, value))) => {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            (None, None) => {}
+        }
+    });
+
+    let mut transactions: Vec<Transaction> = Vec::new();
+    fields.iter().for_each(|(tag, value)| match tag.as_str() {
+        "61" => {
+            if let Some((date, amount)) = mt940_amount(value) {
+                transactions.push(Transaction {
+                    date,
+                    amount,
+                    description: String::new(),
+                });
+            }
+        }
+        // the details of the transaction above
+        "86" => {
+            if let Some(transaction) = transactions.last_mut() {
+                transaction.description = value.clone();
+            }
+        }
+        _ => {}
+    });
+    if transactions.is_empty() {
+        return Err("Fișierul MT940 nu conține nicio tranzacție.".to_string());
+    }
+    Ok(transactions)
+}
+
+fn import_mt940(mt940_file: &Path) -> Result<Vec<Transaction>, String> {
+    let bytes = std::fs::read(mt940_file)
+        .map_err(|error| format!("Fișierul MT940 nu a putut fi citit : {}", error))?;
+    parse_mt940(&String::from_utf8_lossy(&bytes))
+}
+
+pub(crate) fn import_statement(
+    statement_file: &Path,
+    import: &ImportArguments,
+    arguments: &ReconcileArguments,
+) -> Result<Vec<Transaction>, String> {
+    let extension = statement_file
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("csv") => import_csv(statement_file, import, arguments),
+        Some("sta" | "mt940" | "940" | "txt") => import_mt940(statement_file),
+        _ => Err(format!(
+            "Extrasul de cont trebuie să fie în format CSV sau MT940 (.sta, .mt940) : {}",
+            statement_file.display()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::arguments::{Arguments, Column};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn reads_the_amounts_of_mt940_lines() {
+        assert_eq!(
+            mt940_amount("2307110711D1547,06NTRFNONREF"),
+            Some((date(2023, 7, 11), -1547.06))
+        );
+        assert_eq!(
+            mt940_amount("230805C5000,NTRFNONREF"),
+            Some((date(2023, 8, 5), 5000.0))
+        );
+        // the funds code after the mark and the reversals
+        assert_eq!(
+            mt940_amount("2309300930DR5,00NCOMNONREF"),
+            Some((date(2023, 9, 30), -5.0))
+        );
+        assert_eq!(
+            mt940_amount("2309300930RC12,50NTRFNONREF"),
+            Some((date(2023, 9, 30), -12.5))
+        );
+        assert_eq!(mt940_amount("2313010101D5,00NTRF"), None);
+        assert_eq!(mt940_amount("230930X5,00NTRF"), None);
+    }
+
+    #[test]
+    fn reads_the_transactions_of_mt940_statements() {
+        let transactions = parse_mt940(include_str!("../tests/fixtures/mt940.txt")).unwrap();
+        assert_eq!(
+            transactions
+                .iter()
+                .map(|transaction| (
+                    transaction.date,
+                    transaction.amount,
+                    transaction.description.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    date(2023, 7, 11),
+                    -1547.06,
+                    "Plata rata credit RB-2023/4567"
+                ),
+                (date(2023, 8, 5), 5000.0, "Salariu"),
+                (date(2023, 8, 16), -1547.06, "Plata rata credit"),
+                (date(2023, 9, 30), -5.0, "Comision"),
+            ]
+        );
+        assert!(parse_mt940(":20:STATEMENT\n:62F:C230930RON1900,88\n").is_err());
+    }
+
+    fn read(csv: &[u8], amount_column: &str) -> Result<Vec<(NaiveDate, f64)>, String> {
+        let import = Arguments::try_parse_from(["calculator", "scadentar*.csv"])
+            .unwrap()
+            .import;
+        let arguments = ReconcileArguments {
+            statement: PathBuf::from("extras.csv"),
+            description: None,
+            amount_column: Column::Header(amount_column.to_string()),
+            description_column: None,
+            early_days: 10,
+        };
+        read_csv(csv, &import, &arguments).map(|transactions| {
+            transactions
+                .iter()
+                .map(|transaction| (transaction.date, transaction.amount))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn keeps_the_signs_of_a_single_amount_column() {
+        // a statement with credits only is not turned into one with debits
+        assert_eq!(
+            read(
+                b"Data,Descriere,Suma\n01.07.2023,Sold initial,0\n05.07.2023,Salariu,5000.00\n",
+                "Suma"
+            ),
+            Ok(vec![(date(2023, 7, 1), 0.0), (date(2023, 7, 5), 5000.0)])
+        );
+        assert_eq!(
+            read(
+                b"Data,Descriere,Suma\n11.07.2023,Plata rata,-1547.06\n",
+                "Suma"
+            ),
+            Ok(vec![(date(2023, 7, 11), -1547.06)])
+        );
+    }
+
+    #[test]
+    fn reads_debit_and_credit_columns() {
+        assert_eq!(
+            read(
+                b"Data,Descriere,Debit,Credit\n\
+                 11.07.2023,Plata rata,1547.06,\n\
+                 05.08.2023,Salariu,,5000.00\n\
+                 16.08.2023,Comision,5.00,0\n",
+                "Debit"
+            ),
+            Ok(vec![
+                (date(2023, 7, 11), -1547.06),
+                (date(2023, 8, 5), 5000.0),
+                (date(2023, 8, 16), -5.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_the_rows_that_cannot_be_read() {
+        assert!(
+            read(b"Data,Suma\n11.07.2023,-1547.06\n16.08.2023,\xff\n", "Suma")
+                .unwrap_err()
+                .starts_with("Rândul 3 din fișierul CSV nu a putut fi citit")
+        );
+    }
+}
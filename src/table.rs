@@ -79,3 +79,52 @@ pub(crate) fn dashed_line(
         "-".repeat(total_absolut_column_width),
     );
 }
+
+pub(crate) fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(column, header)| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .chain(std::iter::once(header.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+    let separator = format!(
+        "+-{}-+",
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("-+-")
+    );
+    let header_line = format!(
+        "| {} |",
+        headers
+            .iter()
+            .zip(&widths)
+            .map(|(header, width)| format!("{:width$}", header, width = width))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    );
+
+    rows.iter().enumerate().for_each(|(index, row)| {
+        if index % 12 == 0 {
+            println!("{}", separator);
+            println!("{}", header_line);
+            println!("{}", separator);
+        }
+        println!(
+            "| {} |",
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        );
+    });
+    println!("{}", separator);
+}
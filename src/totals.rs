@@ -0,0 +1,167 @@
+use chrono::Datelike;
+
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+
+// the printed total and the sum of the rounded rows may differ by a cent, plus what floating point
+// sums add
+const TOLERANCE: f64 = 0.01 + 1e-6;
+
+// the sums printed on the "Total" row at the end of the schedule
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PrintedTotals {
+    pub(crate) principal: Option<f64>,
+    pub(crate) interest: Option<f64>,
+    pub(crate) insurance: Option<f64>,
+    pub(crate) commission: Option<f64>,
+}
+
+// the amounts on the last line starting with "Total", without the balance columns left empty
+pub(crate) fn footer_amounts(text: &str, locale: Locale) -> Option<Vec<f64>> {
+    text.lines().rev().find_map(|line| {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if !words.first()?.to_lowercase().starts_with("total") {
+            return None;
+        }
+        let amounts = locale.amounts(&words[1..]);
+        (amounts.len() >= 2).then_some(amounts)
+    })
+}
+
+fn months_between(earlier: &Installment, later: &Installment) -> i32 {
+    (later.date.year() - earlier.date.year()) * 12 + later.date.month() as i32
+        - earlier.date.month() as i32
+}
+
+// the rows that explain a difference: installments read twice, months without an installment
+// and balances that do not follow from the previous row
+fn suspicious_rows(schedule: &Schedule) -> Vec<String> {
+    schedule
+        .installments
+        .windows(2)
+        .filter_map(|pair| {
+            let (previous, current) = (&pair[0], &pair[1]);
+            if previous.date == current.date {
+                return Some(format!("rata din {} apare de două ori", current.date));
+            }
+            if months_between(previous, current) > 1 {
+                return Some(format!(
+                    "lipsesc ratele dintre {} și {}",
+                    previous.date, current.date
+                ));
+            }
+            match (previous.balance, current.balance) {
+                (Some(previous_balance), Some(balance))
+                    if (previous_balance - current.principal - balance).abs() > 0.05 =>
+                {
+                    Some(format!(
+                        "soldul de după rata din {} nu urmează din cel de după rata din {}",
+                        current.date, previous.date
+                    ))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// the columns whose parsed rows do not add up to the total printed by the bank, with the printed
+// total and the sum of the rows
+fn mismatches(schedule: &Schedule) -> Vec<(&'static str, f64, f64)> {
+    let Some(totals) = &schedule.totals else {
+        return Vec::new();
+    };
+    let sum =
+        |amount: fn(&Installment) -> f64| -> f64 { schedule.installments.iter().map(amount).sum() };
+    [
+        (
+            "principal",
+            totals.principal,
+            sum(|installment| installment.principal),
+        ),
+        (
+            "dobândă",
+            totals.interest,
+            sum(|installment| installment.interest),
+        ),
+        (
+            "asigurare",
+            totals.insurance,
+            sum(|installment| installment.insurance),
+        ),
+        (
+            "comisioane",
+            totals.commission,
+            sum(|installment| installment.commission),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(name, printed, parsed)| {
+        let printed = printed?;
+        ((printed - parsed).abs() > TOLERANCE).then_some((name, printed, parsed))
+    })
+    .collect()
+}
+
+// warns when the parsed rows do not add up to the totals printed by the bank; only the PDF parsers
+// read a "Total" row, the CSV, spreadsheet and JSON imports skip it and have nothing to compare
+pub(crate) fn check_totals(schedule: &Schedule) {
+    let mismatches = mismatches(schedule);
+    if mismatches.is_empty() {
+        return;
+    }
+
+    eprintln!();
+    mismatches.iter().for_each(|(name, printed, parsed)| {
+        eprintln!(
+            "Atenție: totalul pentru {} din scadențar este {:.2}, dar ratele citite însumează {:.2} (diferență {:.2}).",
+            name,
+            printed,
+            parsed,
+            printed - parsed
+        );
+    });
+    match suspicious_rows(schedule).as_slice() {
+        [] => eprintln!(
+            "Rândurile lipsă sau duplicate nu au putut fi identificate; verificați scadențarul."
+        ),
+        rows => rows
+            .iter()
+            .for_each(|row| eprintln!("Posibilă cauză: {}.", row)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Months, NaiveDate};
+
+    use super::*;
+
+    fn schedule(principals: &[f64], printed_principal: f64) -> Schedule {
+        let installments = principals
+            .iter()
+            .enumerate()
+            .map(|(index, principal)| {
+                let date = NaiveDate::from_ymd_opt(2024, 1, 15)
+                    .and_then(|date| date.checked_add_months(Months::new(index as u32)))
+                    .unwrap();
+                Installment::new(date, *principal, 0.0)
+            })
+            .collect();
+        Schedule {
+            totals: Some(PrintedTotals {
+                principal: Some(printed_principal),
+                ..PrintedTotals::default()
+            }),
+            ..Schedule::new(installments)
+        }
+    }
+
+    #[test]
+    fn allows_a_cent_of_rounding_whatever_the_number_of_rows() {
+        let principals = [0.1; 120];
+        assert!(mismatches(&schedule(&principals, 12.01)).is_empty());
+        // 120 rows once allowed a difference of 1.20
+        assert_eq!(mismatches(&schedule(&principals, 12.5)).len(), 1);
+    }
+}
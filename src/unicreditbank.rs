@@ -0,0 +1,82 @@
+use crate::loaninfo::LoanInfo;
+use crate::locale::Locale;
+use crate::schedule::{Installment, Schedule};
+use crate::totals::{footer_amounts, PrintedTotals};
+
+pub(crate) fn extract_payment_data(text: &str) -> Schedule {
+    let locale = Locale::detect(text);
+    // Data scadenta | Principal | Dobanda | Comision | Asigurare | Total rata | Sold ramas
+    let installments = text
+        .lines()
+        .filter_map(|line| {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let (date, length) = locale.date_prefix(&words)?;
+            let amounts = locale.amounts(&words[length..]);
+            if amounts.len() != 6 {
+                return None;
+            }
+
+            let principal = amounts[0];
+            let interest = amounts[1];
+            let commission = amounts[2];
+            let insurance = amounts[3];
+            let balance = amounts[5];
+
+            Some(Installment {
+                insurance,
+                commission,
+                balance: Some(balance),
+                ..Installment::new(date, principal, interest)
+            })
+        })
+        .collect();
+
+    Schedule {
+        loan: LoanInfo::from_header(text, locale),
+        // the total row leaves the balance empty:
+        // Total | Principal | Dobanda | Comision | Asigurare | Total rata
+        totals: footer_amounts(text, locale).map(|amounts| PrintedTotals {
+            principal: amounts.first().copied(),
+            interest: amounts.get(1).copied(),
+            insurance: amounts.get(3).copied(),
+            commission: amounts.get(2).copied(),
+        }),
+        ..Schedule::new(installments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/unicredit.txt");
+
+    #[test]
+    fn reads_rows_without_an_installment_number() {
+        let schedule = extract_payment_data(FIXTURE);
+        assert_eq!(schedule.installments.len(), 2);
+
+        let first = &schedule.installments[0];
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2025, 1, 20).unwrap());
+        assert_eq!(first.principal, 500.0);
+        assert_eq!(first.interest, 1000.0);
+        assert_eq!(first.commission, 25.0);
+        assert_eq!(first.insurance, 15.0);
+        assert_eq!(first.balance, Some(199500.0));
+        assert_eq!(schedule.installments[1].principal, 502.5);
+    }
+
+    #[test]
+    fn reads_the_total_row_and_the_header() {
+        let schedule = extract_payment_data(FIXTURE);
+        assert_eq!(schedule.currency(), "EUR");
+        let totals = schedule.totals.unwrap();
+        assert_eq!(totals.principal, Some(1002.5));
+        assert_eq!(totals.interest, Some(1997.5));
+        assert_eq!(totals.commission, Some(50.0));
+        assert_eq!(totals.insurance, Some(30.0));
+        assert_eq!(schedule.loan.contract.as_deref(), Some("UC-2024-3390"));
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, UNIX_EPOCH};
+
+use clap::CommandFactory;
+
+use crate::arguments::{Arguments, WatchArguments};
+use crate::batch;
+
+// kept in every watched directory, one line for every processed file
+const LOG_FILE: &str = "processed.log";
+
+// a file is processed again when its modification time or size change
+#[derive(Clone, Copy, PartialEq)]
+struct FileVersion {
+    modified: u64,
+    size: u64,
+}
+
+fn file_version(path: &Path) -> Option<FileVersion> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(FileVersion {
+        modified: metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_secs(),
+        size: metadata.len(),
+    })
+}
+
+// the latest version processed of every file, by path relative to the directory
+fn read_log(directory: &Path) -> HashMap<PathBuf, FileVersion> {
+    std::fs::read_to_string(directory.join(LOG_FILE))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let version = FileVersion {
+                modified: fields.next()?.parse().ok()?,
+                size: fields.next()?.parse().ok()?,
+            };
+            Some((PathBuf::from(fields.next()?), version))
+        })
+        .collect()
+}
+
+fn append_log(directory: &Path, file: &Path, version: FileVersion) -> Result<(), String> {
+    let log_file = directory.join(LOG_FILE);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_file)
+        .and_then(|mut log| {
+            writeln!(
+                log,
+                "{}\t{}\t{}",
+                version.modified,
+                version.size,
+                file.display()
+            )
+        })
+        .map_err(|error| {
+            format!(
+                "Fișierul {} nu a putut fi scris : {}",
+                log_file.display(),
+                error
+            )
+        })
+}
+
+fn report_file(input_file: &Path) -> PathBuf {
+    let stem = input_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    input_file.with_file_name(format!("{}.raport.txt", stem))
+}
+
+// the options the program was started with, without the input files, the watch command, its
+// options and its directories, so every file is analyzed the same way
+fn file_arguments(command_line: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let command = Arguments::command();
+    let watch_options = command
+        .find_subcommand("watch")
+        .map(|watch| watch.get_arguments().collect::<Vec<_>>())
+        .unwrap_or_default();
+    let takes_value = |name: &str| {
+        command
+            .get_arguments()
+            .chain(watch_options.iter().copied())
+            .any(|option| option.get_long() == Some(name) && option.get_action().takes_values())
+    };
+    let mut arguments = vec![OsString::from("--non-interactive")];
+    let mut words = command_line.into_iter();
+    while let Some(word) = words.next() {
+        let text = word.to_string_lossy().to_string();
+        // the positional arguments are not options
+        let Some(option) = text.strip_prefix("--") else {
+            continue;
+        };
+        // only positional arguments follow "--"
+        if option.is_empty() {
+            break;
+        }
+        let (name, value) = match option.split_once('=') {
+            Some((name, _)) => (name, None),
+            None if takes_value(option) => (option, words.next()),
+            None => (option, None),
+        };
+        let is_watch_option = watch_options
+            .iter()
+            .any(|watch_option| watch_option.get_long() == Some(name));
+        if is_watch_option || name == "non-interactive" {
+            continue;
+        }
+        arguments.push(word);
+        arguments.extend(value);
+    }
+    arguments
+}
+
+// runs the program on the file and writes everything it prints in the report, without colors
+fn process(input_file: &Path, arguments: &[OsString]) -> Result<PathBuf, String> {
+    let executable = std::env::current_exe()
+        .map_err(|error| format!("Programul nu a putut fi găsit : {}", error))?;
+    let report = report_file(input_file);
+    let error = |error: std::io::Error| {
+        format!(
+            "Raportul {} nu a putut fi scris : {}",
+            report.display(),
+            error
+        )
+    };
+    let output = File::create(&report).map_err(error)?;
+    let errors = output.try_clone().map_err(error)?;
+    let status = Command::new(executable)
+        .args(arguments)
+        .arg(input_file)
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::null())
+        .stdout(output)
+        .stderr(errors)
+        .status()
+        .map_err(|error| {
+            format!(
+                "Fișierul {} nu a putut fi procesat : {}",
+                input_file.display(),
+                error
+            )
+        })?;
+    if !status.success() {
+        return Err(format!(
+            "Fișierul {} nu a putut fi procesat, eroarea este în {}",
+            input_file.display(),
+            report.display()
+        ));
+    }
+    Ok(report)
+}
+
+// checks the directories every few seconds and processes the schedules added or changed since
+// the last pass; a file is processed only when it did not change between two passes, so files
+// still being copied are left for later
+pub(crate) fn watch(arguments: &WatchArguments) {
+    let directories = &arguments.directories;
+    if let Some(file) = directories.iter().find(|directory| !directory.is_dir()) {
+        eprintln!("{} nu este un director.", file.display());
+        return;
+    }
+    let file_arguments = file_arguments(std::env::args_os().skip(1));
+    let mut logs = directories
+        .iter()
+        .map(|directory| read_log(directory))
+        .collect::<Vec<_>>();
+    let mut seen: HashMap<PathBuf, FileVersion> = HashMap::new();
+    // the files that failed are not logged, so they are tried again after a restart, but not
+    // before they change
+    let mut failed: HashMap<PathBuf, FileVersion> = HashMap::new();
+    println!(
+        "Se urmăresc directoarele {} (Ctrl+C pentru oprire).",
+        directories
+            .iter()
+            .map(|directory| directory.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    loop {
+        directories
+            .iter()
+            .zip(logs.iter_mut())
+            .for_each(|(directory, log)| {
+                batch::directory_files(directory)
+                    .iter()
+                    .for_each(|input_file| {
+                        let Some(version) = file_version(input_file) else {
+                            return;
+                        };
+                        let relative = input_file
+                            .strip_prefix(directory)
+                            .unwrap_or(input_file)
+                            .to_path_buf();
+                        let stable = seen.insert(input_file.clone(), version) == Some(version);
+                        if !stable
+                            || log.get(&relative) == Some(&version)
+                            || failed.get(input_file) == Some(&version)
+                        {
+                            return;
+                        }
+                        match process(input_file, &file_arguments) {
+                            Ok(report) => {
+                                println!(
+                                    "{} {} : {}",
+                                    chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                                    input_file.display(),
+                                    report.display()
+                                );
+                                if let Err(error) = append_log(directory, &relative, version) {
+                                    eprintln!("{}", error);
+                                }
+                                log.insert(relative, version);
+                            }
+                            Err(error) => {
+                                eprintln!("{}", error);
+                                failed.insert(input_file.clone(), version);
+                            }
+                        }
+                    });
+            });
+        std::thread::sleep(Duration::from_secs(arguments.interval));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn passes_the_options_of_the_watch_command_to_every_file() {
+        let command_line = [
+            "--export-json",
+            "--csv-delimiter",
+            ";",
+            "watch",
+            "scadentare",
+            "--interval",
+            "5",
+            "--csv-balance-column=Sold",
+            "--range",
+            "A5:H300",
+            "--non-interactive",
+        ];
+        let mut arguments = vec![OsString::from("calculator")];
+        arguments.extend(file_arguments(command_line.map(OsString::from)));
+        assert_eq!(
+            arguments[1..],
+            [
+                "--non-interactive",
+                "--export-json",
+                "--csv-delimiter",
+                ";",
+                "--csv-balance-column=Sold",
+                "--range",
+                "A5:H300",
+            ]
+        );
+        arguments.push(OsString::from("scadentare/*.pdf"));
+        let file_args = Arguments::try_parse_from(&arguments).unwrap();
+        assert!(file_args.non_interactive && file_args.export_json);
+        assert!(file_args.command.is_none());
+        assert_eq!(file_args.import.csv_delimiter, ';');
+        assert_eq!(file_args.import.range, Some(((4, 0), (299, 7))));
+    }
+}